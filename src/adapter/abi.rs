@@ -0,0 +1,88 @@
+//! Just enough Solidity ABI encoding for router calls: `uint256` amounts that fit
+//! in 128 bits, addresses, booleans, `bytes` and arrays of those.
+
+use crate::domain::types::Address;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Uint(u128),
+    Address(Address),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    AddressArray(Vec<Address>),
+    BytesArray(Vec<Vec<u8>>),
+}
+
+impl AbiValue {
+    fn is_dynamic(&self) -> bool {
+        matches!(
+            self,
+            AbiValue::Bytes(_) | AbiValue::AddressArray(_) | AbiValue::BytesArray(_)
+        )
+    }
+
+    /// The value's own encoding: the word itself for static values, the tail for dynamic ones.
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            AbiValue::Uint(value) => out.extend_from_slice(&uint_word(*value)),
+            AbiValue::Address(address) => out.extend_from_slice(&address_word(address)),
+            AbiValue::Bool(value) => out.extend_from_slice(&uint_word(*value as u128)),
+            AbiValue::Bytes(bytes) => {
+                out.extend_from_slice(&uint_word(bytes.len() as u128));
+                out.extend_from_slice(bytes);
+                out.resize(out.len() + padding(bytes.len()), 0);
+            }
+            AbiValue::AddressArray(addresses) => {
+                out.extend_from_slice(&uint_word(addresses.len() as u128));
+                for address in addresses {
+                    out.extend_from_slice(&address_word(address));
+                }
+            }
+            AbiValue::BytesArray(items) => {
+                out.extend_from_slice(&uint_word(items.len() as u128));
+                let items: Vec<AbiValue> = items.iter().cloned().map(AbiValue::Bytes).collect();
+                out.extend_from_slice(&encode(&items));
+            }
+        }
+    }
+}
+
+/// `abi.encode(values...)`: static values and offsets in the head, dynamic data in the tail.
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let mut head = Vec::with_capacity(values.len() * 32);
+    let mut tail = Vec::new();
+    for value in values {
+        if value.is_dynamic() {
+            head.extend_from_slice(&uint_word((values.len() * 32 + tail.len()) as u128));
+            value.encode_into(&mut tail);
+        } else {
+            value.encode_into(&mut head);
+        }
+    }
+    head.extend_from_slice(&tail);
+    head
+}
+
+/// Calldata for a call to the function identified by `selector`.
+pub fn encode_call(selector: [u8; 4], args: &[AbiValue]) -> Vec<u8> {
+    let mut out = selector.to_vec();
+    out.extend_from_slice(&encode(args));
+    out
+}
+
+fn uint_word(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Addresses are left-padded to 32 bytes.
+fn address_word(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&address.0);
+    word
+}
+
+fn padding(len: usize) -> usize {
+    (32 - len % 32) % 32
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::domain::events::{EventError, PairEvent, RawLog};
+use crate::domain::types::{Pool, PoolId, Token};
+
+/// Decimals assumed for tokens without an explicit entry, as for most ERC20s.
+pub const DEFAULT_TOKEN_DECIMALS: u32 = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    Event(EventError),
+    Graph(GraphError),
+    /// An amount cannot be represented as a `Decimal` with the token's decimals.
+    AmountOutOfRange(PoolId),
+}
+
+impl Display for ApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplyError::Event(err) => write!(f, "cannot decode event: {}", err),
+            ApplyError::Graph(err) => write!(f, "cannot update graph: {}", err),
+            ApplyError::AmountOutOfRange(id) => {
+                write!(f, "amount out of range for pool {}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+impl From<EventError> for ApplyError {
+    fn from(err: EventError) -> Self {
+        ApplyError::Event(err)
+    }
+}
+
+impl From<GraphError> for ApplyError {
+    fn from(err: GraphError) -> Self {
+        ApplyError::Graph(err)
+    }
+}
+
+/// Applies Uniswap V2 pair logs to the pools of a `Graph`.
+///
+/// The pool is looked up by the address of the emitting pair (see
+/// `PoolId::from_address`). Only `Sync` changes the reserves, setting them
+/// outright: the pair emits it from `_update` with the new reserves before the
+/// `Swap`, `Mint` or `Burn` that caused it, so applying their deltas as well would
+/// count every change twice. Those three are decoded, which rejects malformed
+/// logs, and otherwise left alone. Raw amounts are scaled by the token decimals,
+/// taken from `with_decimals` overrides, then from the graph's token metadata.
+#[derive(Debug, Clone, Default)]
+pub struct EventApplier {
+    decimals: HashMap<Token, u32>,
+}
+
+impl EventApplier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_decimals(mut self, token: Token, decimals: u32) -> Self {
+        self.decimals.insert(token, decimals);
+        self
+    }
+
+    pub fn decimals(&self, token: &Token) -> u32 {
+        self.decimals
+            .get(token)
+            .copied()
+            .unwrap_or(DEFAULT_TOKEN_DECIMALS)
+    }
+
+    /// Decimals of `token`, preferring explicit overrides over the graph's token metadata.
+    fn decimals_in(&self, graph: &Graph, token: &Token) -> u32 {
+        match (self.decimals.get(token), graph.token_info(token)) {
+            (Some(&decimals), _) => decimals,
+            (None, Some(info)) => info.decimals,
+            (None, None) => DEFAULT_TOKEN_DECIMALS,
+        }
+    }
+
+    /// Decodes `log` and applies it to the pool that emitted it.
+    ///
+    /// Returns the pool state before the change, or `None` when the log is not
+    /// a `Sync` or comes from a pair the graph does not track.
+    pub fn apply(&self, graph: &mut Graph, log: &RawLog) -> Result<Option<Arc<Pool>>, ApplyError> {
+        let PairEvent::Sync { reserve0, reserve1 } = PairEvent::decode(log)? else {
+            return Ok(None);
+        };
+        let id = PoolId::from_address(&log.address);
+        let Some(pool) = graph.pool(&id) else {
+            return Ok(None);
+        };
+
+        let amount = |raw: u128, token: &Token| {
+            scale(raw, self.decimals_in(graph, token))
+                .ok_or_else(|| ApplyError::AmountOutOfRange(id.clone()))
+        };
+        let reserve0 = amount(reserve0, &pool.token0)?;
+        let reserve1 = amount(reserve1, &pool.token1)?;
+        Ok(Some(graph.update_reserves(&id, reserve0, reserve1)?))
+    }
+
+    /// Applies `logs` in order and returns how many of them set the reserves of a
+    /// tracked pool.
+    ///
+    /// Stops at the first error; logs before it stay applied.
+    pub fn apply_all<'a>(
+        &self,
+        graph: &mut Graph,
+        logs: impl IntoIterator<Item = &'a RawLog>,
+    ) -> Result<usize, ApplyError> {
+        let mut applied = 0;
+        for log in logs {
+            if self.apply(graph, log)?.is_some() {
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    }
+}
+
+/// `raw` token units as an amount with `decimals` decimals.
+///
+/// `Decimal` mantissas are 96 bits wide while reserves are `uint112`, so the
+/// lowest digits of larger amounts are rounded away; `None` only when the whole
+/// part itself does not fit.
+pub(crate) fn scale(raw: u128, decimals: u32) -> Option<Decimal> {
+    let (mut mantissa, mut decimals) = (raw, decimals);
+    while mantissa >> 96 != 0 {
+        decimals = decimals.checked_sub(1)?;
+        mantissa = mantissa / 10 + u128::from(mantissa % 10 >= 5);
+    }
+    Decimal::try_from_i128_with_scale(i128::try_from(mantissa).ok()?, decimals)
+        .ok()
+        .map(|amount| amount.normalize())
+}
@@ -0,0 +1,270 @@
+//! Replays routes by running Uniswap V2 router and pair bytecode in an embedded
+//! EVM (revm), enabled by the `evm` feature.
+//!
+//! Every hop is a call to the venue router's `getAmountsOut(amountIn, [tokenIn,
+//! tokenOut])`, with the pair's code deployed where the router's `pairFor` looks
+//! for it: at the CREATE2 address of the venue's factory and init code hash,
+//! with the `getReserves` storage slot seeded from the graph. The bytecode is
+//! the deployed runtime code, e.g. read with `PoolStateProvider::code`, so the
+//! output is the contracts' own, not a transcription of their formulas.
+
+use std::collections::HashMap;
+
+use revm::Evm;
+use revm::db::{CacheDB, EmptyDB};
+use revm::primitives::{
+    AccountInfo, Address as EvmAddress, Bytecode, Bytes, ExecutionResult, TxKind, U256, keccak256,
+};
+use rust_decimal::Decimal;
+
+use crate::adapter::event_applier::scale;
+use crate::adapter::graph::Graph;
+use crate::adapter::pair_math::{decimals, to_raw};
+use crate::domain::types::{Address, ChainId, Pool, Route, Token, Venue};
+use crate::domain::venue::VenueConfig;
+use crate::port::verification::{ExecutionError, RouteExecutor};
+
+/// `getAmountsOut(uint256,address[])`.
+const GET_AMOUNTS_OUT: [u8; 4] = [0xd0, 0x6c, 0xa6, 0x1f];
+/// `Error(string)`, the payload of `require` failures.
+const ERROR_STRING: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Storage slot of `UniswapV2Pair`'s packed `reserve0`, `reserve1` and `blockTimestampLast`.
+const RESERVES_SLOT: u64 = 8;
+/// Reserves are `uint112` on chain.
+const MAX_RESERVE: u128 = (1 << 112) - 1;
+const GAS_LIMIT: u64 = 10_000_000;
+
+/// Runtime bytecode of a venue's router and of its pairs, as returned by `eth_getCode`.
+///
+/// The router must have been deployed for `config`'s factory, since `pairFor`
+/// derives pair addresses from the factory and init code hash it was built with.
+#[derive(Debug, Clone)]
+pub struct VenueBytecode {
+    pub config: VenueConfig,
+    pub router: Vec<u8>,
+    pub pair: Vec<u8>,
+}
+
+/// Executes routes with the bytecode of each venue they swap through.
+#[derive(Debug, Clone, Default)]
+pub struct EvmExecutor {
+    venues: HashMap<(ChainId, Venue), VenueBytecode>,
+}
+
+impl EvmExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs swaps through the pools of `bytecode.config`'s venue and chain with `bytecode`.
+    pub fn with_venue(mut self, bytecode: VenueBytecode) -> Self {
+        let key = (bytecode.config.chain_id, bytecode.config.venue.clone());
+        self.venues.insert(key, bytecode);
+        self
+    }
+
+    /// Swaps `amount_in` raw units of `token_in` through `pool` in a fresh EVM.
+    fn swap(
+        &self,
+        graph: &Graph,
+        pool: &Pool,
+        token_in: &Token,
+        amount_in: u128,
+    ) -> Result<u128, ExecutionError> {
+        let revert = |reason: String| ExecutionError::Reverted {
+            pool: pool.id.clone(),
+            reason,
+        };
+        let bytecode = self
+            .venues
+            .get(&(graph.chain_id(), pool.venue.clone()))
+            .ok_or_else(|| {
+                ExecutionError::Unsupported(format!(
+                    "no bytecode for {} on chain {}",
+                    pool.venue,
+                    graph.chain_id()
+                ))
+            })?;
+        let (reserve_in, reserve_out, token_out) = if *token_in == pool.token0 {
+            (pool.reserve0, pool.reserve1, &pool.token1)
+        } else if *token_in == pool.token1 {
+            (pool.reserve1, pool.reserve0, &pool.token0)
+        } else {
+            return Err(revert("INVALID_PATH".to_string()));
+        };
+        let (address_in, address_out) = (address(graph, token_in)?, address(graph, token_out)?);
+        let reserve_in = reserve(pool, reserve_in, decimals(graph, token_in))?;
+        let reserve_out = reserve(pool, reserve_out, decimals(graph, token_out))?;
+
+        // The pair orders its tokens, and so its reserves, by address
+        let (token0, token1, reserve0, reserve1) = if address_in < address_out {
+            (address_in, address_out, reserve_in, reserve_out)
+        } else {
+            (address_out, address_in, reserve_out, reserve_in)
+        };
+        let pair = evm_address(pair_address(&bytecode.config, token0, token1));
+        let router = evm_address(bytecode.config.router);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        deploy(&mut db, router, &bytecode.router);
+        deploy(&mut db, pair, &bytecode.pair);
+        db.insert_account_storage(
+            pair,
+            U256::from(RESERVES_SLOT),
+            U256::from(reserve0) | (U256::from(reserve1) << 112),
+        )
+        .expect("the in-memory database never fails");
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Call(router);
+                tx.data = get_amounts_out(amount_in, address_in, address_out);
+                tx.gas_limit = GAS_LIMIT;
+            })
+            .build();
+        let result = evm
+            .transact()
+            .map_err(|err| ExecutionError::Unsupported(format!("EVM error: {:?}", err)))?
+            .result;
+        match result {
+            ExecutionResult::Success { output, .. } => {
+                last_amount(output.data()).ok_or_else(|| {
+                    revert(format!(
+                        "unexpected output 0x{}",
+                        hex::encode(output.data())
+                    ))
+                })
+            }
+            ExecutionResult::Revert { output, .. } => Err(revert(revert_reason(&output))),
+            ExecutionResult::Halt { reason, .. } => Err(revert(format!("{:?}", reason))),
+        }
+    }
+}
+
+impl RouteExecutor for EvmExecutor {
+    fn execute(
+        &self,
+        graph: &Graph,
+        route: &Route,
+        amount_in: Decimal,
+    ) -> Result<Vec<Decimal>, ExecutionError> {
+        let Some(first) = route.steps.first() else {
+            return Ok(Vec::new());
+        };
+        let mut amount = to_raw(amount_in, decimals(graph, &first.from))?;
+        let mut received = Vec::with_capacity(route.steps.len());
+        for step in &route.steps {
+            // Reserves come from the graph, which may be newer than the route
+            let pool = graph
+                .pool(&step.pool.id)
+                .ok_or_else(|| ExecutionError::UnknownPool(step.pool.id.clone()))?;
+            amount = self.swap(graph, pool, &step.from, amount)?;
+            let decimals = decimals(graph, &step.to);
+            received.push(scale(amount, decimals).ok_or_else(|| {
+                ExecutionError::Unsupported(format!("{} raw units of {}", amount, step.to))
+            })?);
+        }
+        Ok(received)
+    }
+}
+
+/// Address of the pair of `token0` and `token1` (sorted), as computed by
+/// `UniswapV2Library.pairFor`: the CREATE2 address for the factory, the hash of
+/// both tokens as salt and the pair's init code hash.
+pub fn pair_address(config: &VenueConfig, token0: Address, token1: Address) -> Address {
+    let salt = keccak256([token0.0, token1.0].concat());
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(&config.factory.0);
+    preimage.extend_from_slice(salt.as_slice());
+    preimage.extend_from_slice(&config.init_code_hash);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(preimage)[12..]);
+    Address(address)
+}
+
+fn evm_address(address: Address) -> EvmAddress {
+    EvmAddress::from(address.0)
+}
+
+fn deploy(db: &mut CacheDB<EmptyDB>, address: EvmAddress, code: &[u8]) {
+    let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+    db.insert_account_info(
+        address,
+        AccountInfo {
+            code_hash: code.hash_slow(),
+            code: Some(code),
+            ..AccountInfo::default()
+        },
+    );
+}
+
+fn address(graph: &Graph, token: &Token) -> Result<Address, ExecutionError> {
+    graph
+        .token_info(token)
+        .map(|info| info.address)
+        .ok_or_else(|| ExecutionError::Unsupported(format!("no address for {}", token)))
+}
+
+fn reserve(pool: &Pool, amount: Decimal, decimals: u32) -> Result<u128, ExecutionError> {
+    let raw = to_raw(amount, decimals)?;
+    if raw > MAX_RESERVE {
+        return Err(ExecutionError::Unsupported(format!(
+            "reserve {} of {} exceeds uint112",
+            amount, pool.id
+        )));
+    }
+    Ok(raw)
+}
+
+/// Calldata of `getAmountsOut(amount_in, [token_in, token_out])`.
+fn get_amounts_out(amount_in: u128, token_in: Address, token_out: Address) -> Bytes {
+    let mut data = GET_AMOUNTS_OUT.to_vec();
+    for word in [
+        U256::from(amount_in),
+        U256::from(0x40),
+        U256::from(2),
+        U256::from_be_slice(&token_in.0),
+        U256::from_be_slice(&token_out.0),
+    ] {
+        data.extend_from_slice(&word.to_be_bytes::<32>());
+    }
+    data.into()
+}
+
+/// The last element of an ABI encoded `uint256[]`, if it fits in 128 bits.
+fn last_amount(output: &[u8]) -> Option<u128> {
+    let word = |index: usize| {
+        let start = index.checked_mul(32)?;
+        output
+            .get(start..start.checked_add(32)?)
+            .map(U256::from_be_slice)
+    };
+    // The offset points at the length, followed by the elements
+    let offset = usize::try_from(word(0)?).ok()?;
+    if offset % 32 != 0 {
+        return None;
+    }
+    let length = usize::try_from(word(offset / 32)?).ok()?;
+    if length == 0 {
+        return None;
+    }
+    u128::try_from(word(offset / 32 + length)?).ok()
+}
+
+/// The message of an `Error(string)` revert, or its raw data.
+fn revert_reason(output: &[u8]) -> String {
+    let message = output
+        .strip_prefix(&ERROR_STRING)
+        .and_then(|payload| {
+            let length = usize::try_from(U256::from_be_slice(payload.get(32..64)?)).ok()?;
+            payload.get(64..64usize.checked_add(length)?)
+        })
+        .and_then(|message| std::str::from_utf8(message).ok());
+    match message {
+        Some(message) => message.to_string(),
+        None if output.is_empty() => "reverted without a reason".to_string(),
+        None => format!("reverted with 0x{}", hex::encode(output)),
+    }
+}
@@ -1,35 +1,145 @@
-use crate::domain::types::{Pool, Token};
+use crate::adapter::liquidity::{self, PruneReport};
+use crate::domain::types::{
+    Address, ChainId, ExecutionParams, LiquidityThresholds, MAINNET, Pool, PoolId, Token,
+    TokenInfo, register_names,
+};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
+use rust_decimal::Decimal;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    UnknownPool(PoolId),
+    DuplicatePool(PoolId),
+    /// The pool belongs to another chain than the graph.
+    ChainMismatch {
+        pool: PoolId,
+        expected: ChainId,
+        actual: ChainId,
+    },
+    /// Two tokens with different addresses share a symbol.
+    DuplicateSymbol {
+        symbol: Token,
+        first: Address,
+        second: Address,
+    },
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::UnknownPool(id) => write!(f, "unknown pool {}", id),
+            GraphError::DuplicatePool(id) => write!(f, "pool {} is already in the graph", id),
+            GraphError::ChainMismatch {
+                pool,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "pool {} is on chain {}, not on the graph's chain {}",
+                pool, actual, expected
+            ),
+            GraphError::DuplicateSymbol {
+                symbol,
+                first,
+                second,
+            } => write!(f, "symbol {} names both {} and {}", symbol, first, second),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Debug, Clone)]
 pub struct Graph {
-    pub adjacency: HashMap<Token, Vec<Arc<Pool>>>,
+    pub(crate) adjacency: HashMap<Token, Vec<Arc<Pool>>>,
+    pub(crate) pools: HashMap<PoolId, Arc<Pool>>,
+    pub(crate) block_number: u64,
+    pub(crate) token_info: HashMap<Token, TokenInfo>,
+    pub(crate) chain_id: ChainId,
+    pub(crate) liquidity: LiquidityThresholds,
+    pub(crate) pruned: Arc<PruneReport>,
+    pub(crate) version: u64,
+}
+
+/// Source of graph versions, shared by every graph of the process.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
 }
 
 /// Represents a graph structure where tokens are connected via pools.
 ///
 /// # Fields
 ///
+/// Fields are read through accessors of the same name and only changed through
+/// the methods below, so that `version` follows every change.
+///
 /// - `adjacency`: A `HashMap` where the keys are tokens and the values are vectors of pools
 ///   that connect to the respective token.
+/// - `pools`: A `HashMap` indexing every pool of the graph by its `PoolId`.
+/// - `block_number`: The block whose state the graph reflects (0 when unknown).
+/// - `token_info`: Token metadata (address, decimals, tags), typically imported from a
+///   token list. Tokens without an entry are still routable.
+/// - `chain_id`: The chain every pool of the graph lives on; pools of other chains are
+///   rejected.
+/// - `liquidity`: Thresholds every pool must meet to be routed through, and `pruned`
+///   the pools currently failing them.
+/// - `version`: Changes whenever the pools or the pruned pools change through the
+///   graph's methods, and is never reused by another graph of the process.
 ///
 /// # Methods
 ///
 /// - `new`: Constructs a new `Graph` instance from a slice of pools. It builds the adjacency
 ///   list by associating each token in the pools with the corresponding pool.
 ///
+/// - `try_new`: Same as `new`, failing instead of panicking on pools of several chains or
+///   duplicate pool IDs.
+///
 /// - `neighbors`: Returns an iterator over the neighbors of a given token. Each neighbor is
 ///   represented as a tuple containing the other token in the pool and a reference to the pool.
 ///
+/// - `routable_neighbors`: Same as `neighbors`, restricted to the hops a route to a given
+///   target may take under the `ExecutionParams` of a request and the graph's liquidity
+///   thresholds. Search algorithms expand through this method.
+///
 /// - `tokens`: Returns a `HashSet` containing all the tokens present in the graph.
+///
+/// - `pool`: Looks up a pool by its `PoolId`.
+///
+/// - `update_reserves`, `insert_pool`, `remove_pool`: Mutate the graph in place, keeping
+///   the adjacency list and the pool index consistent without rebuilding the graph.
 impl Graph {
+    /// Builds the graph of pools known to be consistent, e.g. built by the caller.
+    ///
+    /// Pools read from files, requests or nodes go through `try_new` instead.
+    ///
+    /// # Panics
+    ///
+    /// When the pools are not all on the same chain or two pools share an ID.
     pub fn new(pools: &[Arc<Pool>]) -> Self {
+        Self::try_new(pools).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Builds the graph of `pools` on their chain, mainnet when there are none.
+    ///
+    /// Fails on pools of several chains and on pools sharing an ID.
+    pub fn try_new(pools: &[Arc<Pool>]) -> Result<Self, GraphError> {
+        let chain_id = pools.first().map_or(MAINNET, |pool| pool.chain_id);
         let mut adjacency: HashMap<Token, Vec<Arc<Pool>>> = HashMap::new();
+        let mut index: HashMap<PoolId, Arc<Pool>> = HashMap::new();
 
+        register_names(pools.iter().flat_map(|pool| names(pool)));
         for pool in pools {
+            check_chain(chain_id, pool)?;
+            if index.insert(pool.id.clone(), Arc::clone(pool)).is_some() {
+                return Err(GraphError::DuplicatePool(pool.id.clone()));
+            }
             adjacency
                 .entry(pool.token0.clone())
                 .or_default()
@@ -40,7 +150,75 @@ impl Graph {
                 .push(Arc::clone(pool));
         }
 
-        Self { adjacency }
+        Ok(Self {
+            adjacency,
+            pools: index,
+            block_number: 0,
+            token_info: HashMap::new(),
+            chain_id,
+            liquidity: LiquidityThresholds::default(),
+            pruned: Arc::default(),
+            version: next_version(),
+        })
+    }
+
+    /// An empty graph of `chain_id`.
+    pub fn empty(chain_id: ChainId) -> Self {
+        Self {
+            chain_id,
+            ..Self::new(&[])
+        }
+    }
+
+    /// Excludes pools failing `thresholds` from routing; see `prune`.
+    pub fn with_liquidity(mut self, thresholds: LiquidityThresholds) -> Self {
+        self.liquidity = thresholds;
+        self.prune();
+        self
+    }
+
+    /// Re-checks every pool against the graph's liquidity thresholds, e.g. after
+    /// reserves changed, and returns the pools now excluded from routing.
+    pub fn prune(&mut self) -> &PruneReport {
+        self.pruned = Arc::new(liquidity::assess(self, &self.liquidity));
+        self.version = next_version();
+        &self.pruned
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Every pool of the graph, in no particular order.
+    pub fn pools(&self) -> impl ExactSizeIterator<Item = &Arc<Pool>> {
+        self.pools.values()
+    }
+
+    pub fn liquidity(&self) -> &LiquidityThresholds {
+        &self.liquidity
+    }
+
+    /// Pools currently failing the graph's liquidity thresholds.
+    pub fn pruned(&self) -> &PruneReport {
+        &self.pruned
+    }
+
+    /// Version of the routable state of the graph.
+    ///
+    /// Two graphs with the same version route identically, so results computed
+    /// against one can be reused for the other.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Tags the graph with the block whose state it reflects.
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block_number = block_number;
+        self
     }
 
     /// Constructs a new `Graph` instance from a slice of pools.
@@ -63,8 +241,182 @@ impl Graph {
             })
     }
 
+    /// Neighbors of `token` that a route to `target` under `params` may move to.
+    ///
+    /// Every neighbor but `target` would be an intermediate token of the route.
+    pub fn routable_neighbors<'a>(
+        &'a self,
+        token: &'a Token,
+        target: &'a Token,
+        params: &'a ExecutionParams,
+    ) -> impl ParallelIterator<Item = (Token, Arc<Pool>)> + 'a {
+        let allowed = params.allows_token(token) && self.allows_address(token, params);
+        self.neighbors(token).filter(move |(next, pool)| {
+            let next_allowed = if next == target {
+                params.allows_token(next)
+            } else {
+                params.allows_intermediate(next)
+            } && self.allows_address(next, params);
+            allowed && next_allowed && params.allows_pool(pool) && !self.pruned.is_pruned(&pool.id)
+        })
+    }
+
+    /// Returns `true` if the address recorded for `token` passes
+    /// `params.allowed_addresses`; tokens without metadata never do.
+    fn allows_address(&self, token: &Token, params: &ExecutionParams) -> bool {
+        params.allowed_addresses.as_ref().is_none_or(|allowed| {
+            self.token_info(token)
+                .is_some_and(|info| allowed.contains(&info.address))
+        })
+    }
+
     /// Returns a `HashSet` containing all the tokens present in the graph.
     pub fn tokens(&self) -> HashSet<&Token> {
         self.adjacency.keys().collect()
     }
+
+    /// Returns the metadata known for `token`, if any.
+    pub fn token_info(&self, token: &Token) -> Option<&TokenInfo> {
+        self.token_info.get(token)
+    }
+
+    /// Records metadata for the given tokens, replacing earlier entries for the same address.
+    ///
+    /// Pools name tokens by symbol, so a symbol already bound to another address
+    /// is rejected. Metadata of tokens deployed on other chains is ignored.
+    pub fn with_token_info(
+        mut self,
+        infos: impl IntoIterator<Item = TokenInfo>,
+    ) -> Result<Self, GraphError> {
+        for info in infos
+            .into_iter()
+            .filter(|info| info.chain_id == self.chain_id)
+        {
+            if let Some(existing) = self.token_info.get(&info.symbol)
+                && existing.address != info.address
+            {
+                return Err(GraphError::DuplicateSymbol {
+                    symbol: info.symbol,
+                    first: existing.address,
+                    second: info.address,
+                });
+            }
+            self.token_info.insert(info.symbol.clone(), info);
+        }
+        self.version = next_version();
+        Ok(self)
+    }
+
+    /// Returns the pool registered under `id`, if any.
+    pub fn pool(&self, id: &PoolId) -> Option<&Arc<Pool>> {
+        self.pools.get(id)
+    }
+
+    /// Replaces the reserves of pool `id` and returns the previous pool state.
+    ///
+    /// Pools are immutable behind their `Arc`, so the pool is swapped for an updated
+    /// copy in both the index and the adjacency list. Routes computed earlier keep
+    /// pointing at the old state.
+    pub fn update_reserves(
+        &mut self,
+        id: &PoolId,
+        reserve0: Decimal,
+        reserve1: Decimal,
+    ) -> Result<Arc<Pool>, GraphError> {
+        let current = self
+            .pools
+            .get(id)
+            .ok_or_else(|| GraphError::UnknownPool(id.clone()))?;
+        let updated = Arc::new(Pool {
+            reserve0,
+            reserve1,
+            ..(**current).clone()
+        });
+        self.replace_pool(updated)
+    }
+
+    /// Adds a new pool to the graph.
+    pub fn insert_pool(&mut self, pool: Arc<Pool>) -> Result<(), GraphError> {
+        check_chain(self.chain_id, &pool)?;
+        if self.pools.contains_key(&pool.id) {
+            return Err(GraphError::DuplicatePool(pool.id.clone()));
+        }
+        register_names(names(&pool));
+        for token in [&pool.token0, &pool.token1] {
+            self.adjacency
+                .entry(token.clone())
+                .or_default()
+                .push(Arc::clone(&pool));
+        }
+        self.pools.insert(pool.id.clone(), pool);
+        self.version = next_version();
+        Ok(())
+    }
+
+    /// Removes pool `id` from the graph and returns it.
+    ///
+    /// Tokens left without any pool are dropped from the adjacency list.
+    pub fn remove_pool(&mut self, id: &PoolId) -> Result<Arc<Pool>, GraphError> {
+        let removed = self
+            .pools
+            .remove(id)
+            .ok_or_else(|| GraphError::UnknownPool(id.clone()))?;
+        for token in [&removed.token0, &removed.token1] {
+            if let Some(pools) = self.adjacency.get_mut(token) {
+                pools.retain(|pool| pool.id != *id);
+                if pools.is_empty() {
+                    self.adjacency.remove(token);
+                }
+            }
+        }
+        self.version = next_version();
+        Ok(removed)
+    }
+
+    /// Swaps the pool sharing `pool.id` for `pool` and returns the previous state.
+    ///
+    /// If the tokens changed, the pool is also moved in the adjacency list.
+    pub fn replace_pool(&mut self, pool: Arc<Pool>) -> Result<Arc<Pool>, GraphError> {
+        check_chain(self.chain_id, &pool)?;
+        let previous = self
+            .pools
+            .get(&pool.id)
+            .ok_or_else(|| GraphError::UnknownPool(pool.id.clone()))?;
+        if previous.token0 != pool.token0 || previous.token1 != pool.token1 {
+            let previous = self.remove_pool(&pool.id)?;
+            self.insert_pool(pool)?;
+            return Ok(previous);
+        }
+
+        for token in [&pool.token0, &pool.token1] {
+            if let Some(slot) = self
+                .adjacency
+                .get_mut(token)
+                .and_then(|pools| pools.iter_mut().find(|p| p.id == pool.id))
+            {
+                *slot = Arc::clone(&pool);
+            }
+        }
+        self.version = next_version();
+        Ok(self
+            .pools
+            .insert(pool.id.clone(), pool)
+            .expect("pool presence checked above"))
+    }
+}
+
+/// Names a pool is referred to by in requests.
+fn names(pool: &Pool) -> [&'static str; 4] {
+    [pool.id.0, pool.token0.0, pool.token1.0, pool.venue.0]
+}
+
+fn check_chain(chain_id: ChainId, pool: &Pool) -> Result<(), GraphError> {
+    if pool.chain_id == chain_id {
+        return Ok(());
+    }
+    Err(GraphError::ChainMismatch {
+        pool: pool.id.clone(),
+        expected: chain_id,
+        actual: pool.chain_id,
+    })
 }
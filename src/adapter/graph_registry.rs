@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::graph_store::GraphStore;
+use crate::domain::types::ChainId;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    UnknownChain(ChainId),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::UnknownChain(chain_id) => write!(f, "no graph for chain {}", chain_id),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// One `GraphStore` per chain served by the process.
+///
+/// Every store only ever holds graphs of its own chain: graphs are published
+/// through `publish`, which picks the store from the graph's chain, and graphs
+/// reject pools of other chains. A request names its chain and is routed on
+/// that chain's snapshot alone, so pools of different networks never meet in
+/// one search.
+#[derive(Default, Clone)]
+pub struct GraphRegistry {
+    stores: BTreeMap<ChainId, Arc<GraphStore>>,
+}
+
+impl GraphRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves `graph` on its chain, replacing any store previously registered for it.
+    pub fn with_graph(self, graph: Graph) -> Self {
+        self.with_store(Arc::new(GraphStore::new(graph)))
+    }
+
+    /// Serves the chain of the graph currently in `store` from `store`.
+    pub fn with_store(mut self, store: Arc<GraphStore>) -> Self {
+        self.stores.insert(store.snapshot().chain_id, store);
+        self
+    }
+
+    /// Chains served, in ascending order.
+    pub fn chains(&self) -> impl Iterator<Item = ChainId> + '_ {
+        self.stores.keys().copied()
+    }
+
+    pub fn stores(&self) -> impl Iterator<Item = &Arc<GraphStore>> {
+        self.stores.values()
+    }
+
+    pub fn store(&self, chain_id: ChainId) -> Result<&Arc<GraphStore>, RegistryError> {
+        self.stores
+            .get(&chain_id)
+            .ok_or(RegistryError::UnknownChain(chain_id))
+    }
+
+    /// Latest published graph of `chain_id`.
+    pub fn snapshot(&self, chain_id: ChainId) -> Result<Arc<Graph>, RegistryError> {
+        self.store(chain_id).map(|store| store.snapshot())
+    }
+
+    /// Publishes `graph` to the store of its chain, which must be registered.
+    pub fn publish(&self, graph: Graph) -> Result<(), RegistryError> {
+        self.store(graph.chain_id)?.publish(graph);
+        Ok(())
+    }
+}
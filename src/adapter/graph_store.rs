@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use arc_swap::ArcSwap;
+
+use crate::adapter::graph::Graph;
+
+/// Publishes immutable, versioned `Graph` snapshots to concurrent readers.
+///
+/// Readers call `snapshot` and keep a consistent graph for as long as they hold
+/// the returned `Arc`, whatever the writer does in the meantime. Writers build
+/// the next version on a private copy of the latest graph and publish it with a
+/// single atomic pointer swap, so a route is never computed against a
+/// half-applied block. Reads are lock-free; writers are serialized so that
+/// concurrent updates cannot overwrite each other.
+pub struct GraphStore {
+    current: ArcSwap<Graph>,
+    writer: Mutex<()>,
+    listeners: Mutex<Vec<Listener>>,
+}
+
+/// Called with every newly published graph; returns `false` to unsubscribe.
+type Listener = Box<dyn Fn(&Arc<Graph>) -> bool + Send>;
+
+impl GraphStore {
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(graph),
+            writer: Mutex::new(()),
+            listeners: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Calls `listener` after each publish, in publication order, until it returns `false`.
+    ///
+    /// Listeners run on the writer's thread while it still holds the write lock,
+    /// so they should only hand the graph over, e.g. through a channel.
+    pub fn on_publish(&self, listener: impl Fn(&Arc<Graph>) -> bool + Send + 'static) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Box::new(listener));
+    }
+
+    /// Returns the latest published graph.
+    pub fn snapshot(&self) -> Arc<Graph> {
+        self.current.load_full()
+    }
+
+    /// Block number of the latest published graph.
+    pub fn block_number(&self) -> u64 {
+        self.current.load().block_number
+    }
+
+    /// Publishes `graph` as the new version, replacing the current one wholesale.
+    pub fn publish(&self, graph: Graph) {
+        let _guard = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.store(graph);
+    }
+
+    /// Applies `update` to a copy of the latest graph and, if it succeeds, publishes
+    /// the result tagged with `block_number`. Pools are re-checked against the
+    /// graph's liquidity thresholds before publishing.
+    ///
+    /// When `update` fails nothing is published and readers keep seeing the
+    /// previous version.
+    pub fn update<T, E>(
+        &self,
+        block_number: u64,
+        update: impl FnOnce(&mut Graph) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let _guard = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = Graph::clone(&self.current.load());
+        let result = update(&mut next)?;
+        next.block_number = block_number;
+        if !next.liquidity.is_empty() {
+            next.prune();
+        }
+        self.store(next);
+        Ok(result)
+    }
+
+    fn store(&self, graph: Graph) {
+        let graph = Arc::new(graph);
+        self.current.store(Arc::clone(&graph));
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|listener| listener(&graph));
+    }
+}
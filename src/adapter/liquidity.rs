@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::domain::types::{ExecutionParams, LiquidityThresholds, Pool, PoolId, Token};
+
+/// Why a pool was excluded from routing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PruneReason {
+    /// One of the reserves is below `min_reserve`.
+    LowReserve,
+    /// The pool's value is below `min_tvl`.
+    LowTvl,
+    /// Neither token can be priced in the numeraire, so the TVL is unknown.
+    Unpriced,
+}
+
+impl Display for PruneReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneReason::LowReserve => write!(f, "below min reserve"),
+            PruneReason::LowTvl => write!(f, "below min TVL"),
+            PruneReason::Unpriced => write!(f, "unpriced"),
+        }
+    }
+}
+
+/// Pools of a graph failing a set of `LiquidityThresholds`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PruneReport {
+    /// Pools checked against the thresholds.
+    pub checked: usize,
+    pub pruned: HashMap<PoolId, PruneReason>,
+}
+
+impl PruneReport {
+    pub fn is_pruned(&self, id: &PoolId) -> bool {
+        self.pruned.contains_key(id)
+    }
+
+    /// Number of pools pruned for `reason`.
+    pub fn count(&self, reason: PruneReason) -> usize {
+        self.pruned.values().filter(|&&r| r == reason).count()
+    }
+}
+
+impl Display for PruneReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} pools pruned ({} {}, {} {}, {} {})",
+            self.pruned.len(),
+            self.checked,
+            self.count(PruneReason::LowReserve),
+            PruneReason::LowReserve,
+            self.count(PruneReason::LowTvl),
+            PruneReason::LowTvl,
+            self.count(PruneReason::Unpriced),
+            PruneReason::Unpriced
+        )
+    }
+}
+
+/// Checks every pool of `graph` against `thresholds`.
+///
+/// Pools below `min_reserve` are pruned first and take no part in pricing, so
+/// dust pools cannot set the prices the TVL of other pools is measured with.
+pub fn assess(graph: &Graph, thresholds: &LiquidityThresholds) -> PruneReport {
+    if thresholds.is_empty() {
+        return PruneReport::default();
+    }
+    let mut pruned: HashMap<PoolId, PruneReason> = graph
+        .pools
+        .values()
+        .filter(|pool| !thresholds.has_min_reserve(pool))
+        .map(|pool| (pool.id.clone(), PruneReason::LowReserve))
+        .collect();
+
+    if let Some(min_tvl) = &thresholds.min_tvl {
+        let prices = prices_through(graph, &min_tvl.numeraire, |pool| {
+            !pruned.contains_key(&pool.id)
+        });
+        let low_tvl: Vec<(PoolId, PruneReason)> = graph
+            .pools
+            .values()
+            .filter(|pool| !pruned.contains_key(&pool.id))
+            .filter_map(|pool| match tvl(pool, &prices) {
+                None => Some((pool.id.clone(), PruneReason::Unpriced)),
+                Some(value) if value < min_tvl.value => {
+                    Some((pool.id.clone(), PruneReason::LowTvl))
+                }
+                Some(_) => None,
+            })
+            .collect();
+        pruned.extend(low_tvl);
+    }
+
+    PruneReport {
+        checked: graph.pools.len(),
+        pruned,
+    }
+}
+
+/// `params` with the pools failing its liquidity thresholds moved to
+/// `excluded_pools`, along with the report of those pools.
+///
+/// The returned parameters have no thresholds left, so applying them again is
+/// free: search entry points call this once per request and pass the result
+/// down, and traversal only has to look pools up instead of pricing the graph.
+pub fn apply_thresholds(graph: &Graph, params: ExecutionParams) -> (ExecutionParams, PruneReport) {
+    if params.liquidity.is_empty() {
+        return (params, PruneReport::default());
+    }
+    let report = assess(graph, &params.liquidity);
+    let mut excluded: HashSet<PoolId> = params
+        .excluded_pools
+        .as_deref()
+        .cloned()
+        .unwrap_or_default();
+    excluded.extend(report.pruned.keys().cloned());
+    let params = ExecutionParams {
+        excluded_pools: Some(Arc::new(excluded)),
+        liquidity: LiquidityThresholds::default(),
+        ..params
+    };
+    (params, report)
+}
+
+/// Spot price of every token connected to `numeraire`, in units of `numeraire`.
+pub fn spot_prices(graph: &Graph, numeraire: &Token) -> HashMap<Token, Decimal> {
+    prices_through(graph, numeraire, |_| true)
+}
+
+/// Prices spread out from the numeraire one hop at a time, through `usable` pools.
+///
+/// A token first reached on a hop is priced through the pool holding the most
+/// value of already priced tokens.
+fn prices_through(
+    graph: &Graph,
+    numeraire: &Token,
+    usable: impl Fn(&Pool) -> bool,
+) -> HashMap<Token, Decimal> {
+    let mut prices = HashMap::from([(numeraire.clone(), Decimal::ONE)]);
+    let mut frontier = vec![numeraire.clone()];
+
+    while !frontier.is_empty() {
+        // Next token → (value of the priced side, price)
+        let mut candidates: HashMap<Token, (Decimal, Decimal)> = HashMap::new();
+        for token in &frontier {
+            let price = prices[token];
+            for pool in graph.adjacency.get(token).into_iter().flatten() {
+                let Some(other) = pool.get_other_token(token) else {
+                    continue;
+                };
+                if prices.contains_key(&other) || !usable(pool) {
+                    continue;
+                }
+                let (reserve, other_reserve) = if pool.token0 == *token {
+                    (pool.reserve0, pool.reserve1)
+                } else {
+                    (pool.reserve1, pool.reserve0)
+                };
+                let Some(depth) = reserve.checked_mul(price) else {
+                    continue;
+                };
+                let Some(other_price) = depth.checked_div(other_reserve) else {
+                    continue;
+                };
+                if candidates.get(&other).is_none_or(|(best, _)| depth > *best) {
+                    candidates.insert(other, (depth, other_price));
+                }
+            }
+        }
+        frontier = candidates.keys().cloned().collect();
+        prices.extend(
+            candidates
+                .into_iter()
+                .map(|(token, (_, price))| (token, price)),
+        );
+    }
+    prices
+}
+
+/// Value of both reserves of `pool`; a pool's sides hold equal value at its own
+/// price, so one priced token is enough.
+fn tvl(pool: &Pool, prices: &HashMap<Token, Decimal>) -> Option<Decimal> {
+    let value0 = prices
+        .get(&pool.token0)
+        .and_then(|price| pool.reserve0.checked_mul(*price));
+    let value1 = prices
+        .get(&pool.token1)
+        .and_then(|price| pool.reserve1.checked_mul(*price));
+    match (value0, value1) {
+        (Some(value0), Some(value1)) => value0.checked_add(value1),
+        (Some(value), None) | (None, Some(value)) => value.checked_mul(Decimal::TWO),
+        (None, None) => None,
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, de::Error as _};
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::domain::types::{Address, ChainId, MAINNET, Pool, PoolId, Token, Venue};
+use crate::domain::venue::Venues;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// A malformed row; `line` is 1-based and points into the source file.
+    Row {
+        line: usize,
+        message: String,
+    },
+    /// The file extension is neither `.json`, `.jsonl` nor `.csv`.
+    UnsupportedFormat(String),
+    /// The pools do not form a valid graph.
+    Graph(GraphError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "cannot read pool file: {}", err),
+            LoadError::Row { line, message } => write!(f, "line {}: {}", line, message),
+            LoadError::UnsupportedFormat(path) => write!(f, "unsupported pool file {}", path),
+            LoadError::Graph(err) => write!(f, "invalid pools: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(err: std::io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<GraphError> for LoadError {
+    fn from(err: GraphError) -> Self {
+        LoadError::Graph(err)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Row {
+            line: err.line(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Pools read from a dump, with the token addresses found along the way.
+#[derive(Debug, Clone, Default)]
+pub struct PoolDump {
+    pub pools: Vec<Arc<Pool>>,
+    pub token_addresses: HashMap<Token, Address>,
+    ids: HashSet<PoolId>,
+}
+
+impl PoolDump {
+    /// Builds the graph of the pools.
+    ///
+    /// `pools` is public, so the checks made while parsing may no longer hold;
+    /// they are made again here.
+    pub fn graph(&self) -> Result<Graph, GraphError> {
+        Graph::try_new(&self.pools)
+    }
+
+    /// Validates `record` and appends it, reporting problems against `line`.
+    fn push(&mut self, line: usize, record: PoolRecord) -> Result<(), LoadError> {
+        let row_error = |message: String| LoadError::Row { line, message };
+
+        if record.token0.symbol == record.token1.symbol {
+            return Err(row_error(format!(
+                "pool {} pairs {} with itself",
+                record.id, record.token0.symbol
+            )));
+        }
+        if record.reserve0.is_sign_negative() || record.reserve1.is_sign_negative() {
+            return Err(row_error(format!(
+                "pool {} has a negative reserve",
+                record.id
+            )));
+        }
+        let chain_id = record.chain_id.unwrap_or(MAINNET);
+        if let Some(first) = self.pools.first()
+            && first.chain_id != chain_id
+        {
+            return Err(row_error(format!(
+                "pool {} is on chain {} but earlier pools are on chain {}",
+                record.id, chain_id, first.chain_id
+            )));
+        }
+        let id = PoolId::intern(&record.id);
+        if !self.ids.insert(id.clone()) {
+            return Err(row_error(format!("duplicate pool id {}", record.id)));
+        }
+
+        let mut tokens = Vec::with_capacity(2);
+        for token in [record.token0, record.token1] {
+            let symbol = Token::intern(&token.symbol);
+            if let Some(address) = token.address {
+                match self.token_addresses.get(&symbol) {
+                    Some(known) if *known != address => {
+                        return Err(row_error(format!(
+                            "token {} has address {} but was {} earlier",
+                            symbol, address, known
+                        )));
+                    }
+                    _ => {
+                        self.token_addresses.insert(symbol.clone(), address);
+                    }
+                }
+            }
+            tokens.push(symbol);
+        }
+        let [token0, token1] = <[Token; 2]>::try_from(tokens).expect("two tokens per pool");
+        let venue = record
+            .venue
+            .as_deref()
+            .map_or_else(Venue::default, Venue::intern);
+        let fee_bps = match record.fee_bps {
+            Some(fee_bps) => Decimal::from(fee_bps),
+            None => Venues::builtin().fee_bps(chain_id, &venue).ok_or_else(|| {
+                row_error(format!(
+                    "pool {} has no fee_bps and {} on chain {} has no default fee",
+                    record.id, venue, chain_id
+                ))
+            })?,
+        };
+
+        self.pools.push(Arc::new(Pool {
+            id,
+            token0,
+            token1,
+            reserve0: record.reserve0,
+            reserve1: record.reserve1,
+            fee_bps,
+            venue,
+            chain_id,
+        }));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenRecord {
+    symbol: String,
+    #[serde(default, deserialize_with = "optional_address")]
+    address: Option<Address>,
+}
+
+/// One pool entry of a JSON dump.
+#[derive(Debug, Deserialize)]
+struct PoolRecord {
+    id: String,
+    token0: TokenRecord,
+    token1: TokenRecord,
+    #[serde(deserialize_with = "decimal_string")]
+    reserve0: Decimal,
+    #[serde(deserialize_with = "decimal_string")]
+    reserve1: Decimal,
+    /// The venue's default fee when absent.
+    #[serde(default)]
+    fee_bps: Option<u32>,
+    /// `uniswap` when absent.
+    #[serde(default)]
+    venue: Option<String>,
+    /// Mainnet when absent; every pool of a dump must be on the same chain.
+    #[serde(default)]
+    chain_id: Option<ChainId>,
+}
+
+/// One row of a CSV dump; tokens are flattened into `tokenN_symbol` / `tokenN_address` columns.
+#[derive(Debug, Deserialize)]
+struct CsvRecord {
+    id: String,
+    token0_symbol: String,
+    #[serde(default, deserialize_with = "optional_address")]
+    token0_address: Option<Address>,
+    token1_symbol: String,
+    #[serde(default, deserialize_with = "optional_address")]
+    token1_address: Option<Address>,
+    #[serde(deserialize_with = "decimal_string")]
+    reserve0: Decimal,
+    #[serde(deserialize_with = "decimal_string")]
+    reserve1: Decimal,
+    #[serde(default)]
+    fee_bps: Option<u32>,
+    #[serde(default, deserialize_with = "optional_string")]
+    venue: Option<String>,
+    #[serde(default)]
+    chain_id: Option<ChainId>,
+}
+
+impl From<CsvRecord> for PoolRecord {
+    fn from(row: CsvRecord) -> Self {
+        PoolRecord {
+            id: row.id,
+            token0: TokenRecord {
+                symbol: row.token0_symbol,
+                address: row.token0_address,
+            },
+            token1: TokenRecord {
+                symbol: row.token1_symbol,
+                address: row.token1_address,
+            },
+            reserve0: row.reserve0,
+            reserve1: row.reserve1,
+            fee_bps: row.fee_bps,
+            venue: row.venue,
+            chain_id: row.chain_id,
+        }
+    }
+}
+
+fn decimal_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    Decimal::from_str(raw.trim())
+        .map_err(|_| D::Error::custom(format!("invalid decimal amount {:?}", raw)))
+}
+
+fn optional_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|raw| !raw.trim().is_empty()))
+}
+
+fn optional_address<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Address>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(raw) if !raw.trim().is_empty() => {
+            raw.trim().parse().map(Some).map_err(D::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parses a JSON pool dump.
+///
+/// Accepts either a JSON array of pool objects or JSON lines (one object per line):
+///
+/// ```json
+/// {"id": "eth-usdc-1", "token0": {"symbol": "ETH", "address": "0xc02a…"},
+///  "token1": {"symbol": "USDC"}, "reserve0": "2000", "reserve1": "2000000", "fee_bps": 30}
+/// ```
+///
+/// An optional `chain_id` places the pools on another chain than mainnet, and an
+/// optional `venue` (default `uniswap`) names the exchange. `fee_bps` may be left
+/// out for venues with a known default fee.
+pub fn parse_json(input: &str) -> Result<PoolDump, LoadError> {
+    let mut dump = PoolDump::default();
+
+    if input.trim_start().starts_with('[') {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(input)?;
+        // Locate each entry so that validation errors can still point at a line
+        let lines = entry_lines(input);
+        for (index, entry) in entries.into_iter().enumerate() {
+            let line = lines.get(index).copied().unwrap_or(1);
+            let record = PoolRecord::deserialize(entry).map_err(|err| LoadError::Row {
+                line,
+                message: err.to_string(),
+            })?;
+            dump.push(line, record)?;
+        }
+        return Ok(dump);
+    }
+
+    for (index, raw) in input.lines().enumerate() {
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let line = index + 1;
+        let record: PoolRecord = serde_json::from_str(raw).map_err(|err| LoadError::Row {
+            line,
+            message: err.to_string(),
+        })?;
+        dump.push(line, record)?;
+    }
+    Ok(dump)
+}
+
+/// Parses a CSV pool dump with a header row:
+///
+/// ```text
+/// id,token0_symbol,token0_address,token1_symbol,token1_address,reserve0,reserve1,fee_bps
+/// eth-usdc-1,ETH,0xc02a…,USDC,,2000,2000000,30
+/// ```
+///
+/// Address columns may be left empty or omitted, as may `venue` and `chain_id`
+/// columns; `fee_bps` may be empty for venues with a known default fee.
+pub fn parse_csv(input: &str) -> Result<PoolDump, LoadError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(input.as_bytes());
+    let headers = reader.headers().map_err(|err| csv_error(&err, 1))?.clone();
+    let mut dump = PoolDump::default();
+
+    for row in reader.records() {
+        let row = row.map_err(|err| csv_error(&err, 0))?;
+        let line = row.position().map_or(0, |pos| pos.line() as usize);
+        let record: CsvRecord = row
+            .deserialize(Some(&headers))
+            .map_err(|err| csv_error(&err, line))?;
+        dump.push(line, record.into())?;
+    }
+    Ok(dump)
+}
+
+fn csv_error(err: &csv::Error, fallback_line: usize) -> LoadError {
+    let line = err
+        .position()
+        .map_or(fallback_line, |pos| pos.line() as usize);
+    let message = match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+        _ => err.to_string(),
+    };
+    LoadError::Row { line, message }
+}
+
+/// Reads a pool dump, picking the parser from the file extension.
+pub fn load_pools(path: impl AsRef<Path>) -> Result<PoolDump, LoadError> {
+    let path = path.as_ref();
+    let input = std::fs::read_to_string(path)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json" | "jsonl") => parse_json(&input),
+        Some("csv") => parse_csv(&input),
+        _ => Err(LoadError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+/// Builds a `Graph` straight from a pool dump file.
+pub fn load_graph(path: impl AsRef<Path>) -> Result<Graph, LoadError> {
+    Ok(load_pools(path)?.graph()?)
+}
+
+/// Line on which each top-level object of a JSON array starts.
+fn entry_lines(input: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut line = 1;
+
+    for c in input.chars() {
+        match c {
+            '\n' => line += 1,
+            _ if in_string => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_string = false;
+                }
+            }
+            '"' => in_string = true,
+            '[' | '{' => {
+                if depth == 1 && c == '{' {
+                    lines.push(line);
+                }
+                depth += 1;
+            }
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    lines
+}
@@ -1 +1,17 @@
+pub mod abi;
+pub mod event_applier;
+#[cfg(feature = "evm")]
+pub mod evm;
 pub mod graph;
+pub mod graph_registry;
+pub mod graph_store;
+pub mod liquidity;
+pub mod loader;
+pub mod pair_math;
+pub mod parallel_pools;
+pub mod reorg_journal;
+pub mod router_v2;
+pub mod rpc_provider;
+pub mod snapshot;
+pub mod token_list;
+pub mod universal_router;
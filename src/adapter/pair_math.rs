@@ -0,0 +1,150 @@
+//! Replays routes with the integer arithmetic of `UniswapV2Pair.swap` and
+//! `UniswapV2Library.getAmountOut`, in raw token units.
+//!
+//! Unlike `Pool::get_output_amount` every hop is rounded down to a whole token
+//! unit, exactly as on chain, so comparing both exposes drift in the router's
+//! decimal math. The formulas are transcribed from the contracts, not executed
+//! from their bytecode: a bug shared by both transcriptions goes unnoticed.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::adapter::event_applier::{DEFAULT_TOKEN_DECIMALS, scale};
+use crate::adapter::graph::Graph;
+use crate::domain::types::{Pool, Route, Token};
+use crate::port::verification::{ExecutionError, RouteExecutor};
+
+const FEE_DENOMINATOR: u128 = 10_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PairMath;
+
+impl RouteExecutor for PairMath {
+    fn execute(
+        &self,
+        graph: &Graph,
+        route: &Route,
+        amount_in: Decimal,
+    ) -> Result<Vec<Decimal>, ExecutionError> {
+        let Some(first) = route.steps.first() else {
+            return Ok(Vec::new());
+        };
+        let mut amount = to_raw(amount_in, decimals(graph, &first.from))?;
+        let mut received = Vec::with_capacity(route.steps.len());
+        for step in &route.steps {
+            // Reserves come from the graph, which may be newer than the route
+            let pool = graph
+                .pool(&step.pool.id)
+                .ok_or_else(|| ExecutionError::UnknownPool(step.pool.id.clone()))?;
+            amount = amount_out(graph, pool, &step.from, amount)?;
+            let decimals = decimals(graph, &step.to);
+            received.push(scale(amount, decimals).ok_or_else(|| {
+                ExecutionError::Unsupported(format!("{} raw units of {}", amount, step.to))
+            })?);
+        }
+        Ok(received)
+    }
+}
+
+/// `getAmountOut` with the pool's fee: `in * (10000 - fee) * reserveOut /
+/// (reserveIn * 10000 + in * (10000 - fee))`, rounded down.
+fn amount_out(
+    graph: &Graph,
+    pool: &Pool,
+    token_in: &Token,
+    amount_in: u128,
+) -> Result<u128, ExecutionError> {
+    let revert = |reason: &str| ExecutionError::Reverted {
+        pool: pool.id.clone(),
+        reason: reason.to_string(),
+    };
+    let (reserve_in, reserve_out, token_out) = if *token_in == pool.token0 {
+        (pool.reserve0, pool.reserve1, &pool.token1)
+    } else if *token_in == pool.token1 {
+        (pool.reserve1, pool.reserve0, &pool.token0)
+    } else {
+        return Err(revert("INVALID_PATH"));
+    };
+    let reserve_in = to_raw(reserve_in, decimals(graph, token_in))?;
+    let reserve_out = to_raw(reserve_out, decimals(graph, token_out))?;
+    let fee = fee_bps(pool)?;
+
+    if amount_in == 0 {
+        return Err(revert("INSUFFICIENT_INPUT_AMOUNT"));
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(revert("INSUFFICIENT_LIQUIDITY"));
+    }
+    let overflow = || ExecutionError::Unsupported(format!("amounts overflow in {}", pool.id));
+    let amount_in_with_fee = amount_in
+        .checked_mul(FEE_DENOMINATOR - fee)
+        .ok_or_else(overflow)?;
+    let denominator = reserve_in
+        .checked_mul(FEE_DENOMINATOR)
+        .and_then(|reserve| reserve.checked_add(amount_in_with_fee))
+        .ok_or_else(overflow)?;
+    let amount_out = mul_div(amount_in_with_fee, reserve_out, denominator).ok_or_else(overflow)?;
+    if amount_out == 0 {
+        return Err(revert("INSUFFICIENT_OUTPUT_AMOUNT"));
+    }
+    Ok(amount_out)
+}
+
+fn fee_bps(pool: &Pool) -> Result<u128, ExecutionError> {
+    let fee = pool.fee_bps;
+    fee.fract()
+        .is_zero()
+        .then(|| fee.to_u128())
+        .flatten()
+        .filter(|&fee| fee < FEE_DENOMINATOR)
+        .ok_or_else(|| ExecutionError::Unsupported(format!("fee of {} bps in {}", fee, pool.id)))
+}
+
+pub(crate) fn decimals(graph: &Graph, token: &Token) -> u32 {
+    graph
+        .token_info(token)
+        .map_or(DEFAULT_TOKEN_DECIMALS, |info| info.decimals)
+}
+
+/// `amount` in whole raw units, rounded down.
+pub(crate) fn to_raw(amount: Decimal, decimals: u32) -> Result<u128, ExecutionError> {
+    let rounded = amount.round_dp_with_strategy(decimals, RoundingStrategy::ToZero);
+    u128::try_from(rounded.mantissa())
+        .ok()
+        .zip(10u128.checked_pow(decimals - rounded.scale()))
+        .and_then(|(mantissa, factor)| mantissa.checked_mul(factor))
+        .ok_or_else(|| {
+            ExecutionError::Unsupported(format!("{} with {} decimals", amount, decimals))
+        })
+}
+
+/// `a * b / c` rounded down, through a 256-bit intermediate product.
+///
+/// Returns `None` when `c` is zero or the quotient does not fit in 128 bits.
+fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    let low = (low_low & LOW) | (middle << 64);
+    let high = a_high * b_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    if high >= c {
+        return None;
+    }
+
+    // Long division of (high, low) by c, one bit at a time; the remainder stays below c
+    let (mut remainder, mut quotient) = (high, 0u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::spot_prices;
+use crate::domain::types::{Pool, PoolId, Token};
+
+/// Which of the pools sharing a token pair `prune_parallel_pools` keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParallelPoolPolicy {
+    /// The `n` pools of each pair with the most liquidity (product of the reserves).
+    TopN(usize),
+    /// Every pool of the pair that no other pool beats at every trade size in range.
+    Undominated(TradeSizes),
+}
+
+/// Trade sizes a pool has to be competitive for to be kept.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeSizes {
+    Any,
+    /// Sizes valued in `numeraire` at spot prices. Pairs whose tokens cannot be
+    /// priced are checked at any size.
+    Between {
+        min: Decimal,
+        max: Decimal,
+        numeraire: Token,
+    },
+}
+
+/// A copy of `graph` keeping, for each token pair, only the pools selected by `policy`.
+///
+/// Returns the smaller graph and the IDs of the removed pools, sorted. Block
+/// number, token metadata and liquidity thresholds carry over.
+pub fn prune_parallel_pools(graph: &Graph, policy: &ParallelPoolPolicy) -> (Graph, Vec<PoolId>) {
+    let mut pairs: HashMap<(Token, Token), Vec<Arc<Pool>>> = HashMap::new();
+    for pool in graph.pools.values() {
+        pairs.entry(pair(pool)).or_default().push(Arc::clone(pool));
+    }
+
+    let prices = match policy {
+        ParallelPoolPolicy::Undominated(TradeSizes::Between { numeraire, .. }) => {
+            spot_prices(graph, numeraire)
+        }
+        _ => HashMap::new(),
+    };
+    let mut kept: Vec<Arc<Pool>> = Vec::new();
+    let mut removed: Vec<PoolId> = Vec::new();
+    for ((a, b), mut pools) in pairs {
+        let keep = match policy {
+            ParallelPoolPolicy::TopN(n) => top_n(&mut pools, *n),
+            ParallelPoolPolicy::Undominated(sizes) => {
+                let range = |token: &Token| match sizes {
+                    TradeSizes::Between { min, max, .. } => prices.get(token).and_then(|price| {
+                        Some((min.checked_div(*price)?, max.checked_div(*price)?))
+                    }),
+                    TradeSizes::Any => None,
+                };
+                undominated(&mut pools, [(&a, range(&a)), (&b, range(&b))])
+            }
+        };
+        removed.extend(pools.drain(keep..).map(|pool| pool.id.clone()));
+        kept.append(&mut pools);
+    }
+    kept.sort_by_key(|pool| pool.id.0);
+    removed.sort_by_key(|id| id.0);
+
+    let mut pruned = Graph::empty(graph.chain_id).with_block_number(graph.block_number);
+    for pool in kept {
+        pruned
+            .insert_pool(pool)
+            .expect("pools of one graph are unique and on its chain");
+    }
+    pruned.token_info = graph.token_info.clone();
+    (pruned.with_liquidity(graph.liquidity.clone()), removed)
+}
+
+/// The pair of `pool`, tokens in a fixed order whatever the pool's own order.
+fn pair(pool: &Pool) -> (Token, Token) {
+    if pool.token0.0 <= pool.token1.0 {
+        (pool.token0.clone(), pool.token1.clone())
+    } else {
+        (pool.token1.clone(), pool.token0.clone())
+    }
+}
+
+/// Moves the `n` deepest pools to the front and returns how many to keep.
+fn top_n(pools: &mut [Arc<Pool>], n: usize) -> usize {
+    let depth = |pool: &Pool| {
+        pool.reserve0
+            .checked_mul(pool.reserve1)
+            .unwrap_or(Decimal::MAX)
+    };
+    pools.sort_by(|x, y| depth(y).cmp(&depth(x)).then_with(|| x.id.0.cmp(y.id.0)));
+    n.min(pools.len())
+}
+
+/// Moves the undominated pools to the front and returns how many there are.
+///
+/// With constant product pools, whether one pool outputs more than another is
+/// linear in the trade size once both sides are multiplied by their
+/// denominators, so comparing the outputs at both ends of the size range
+/// decides it for every size in between. For any size, the ends are the
+/// marginal price and the output reserve. A pool is dropped when, in each
+/// direction, another pool does at least as well at both ends; identical pools
+/// are told apart by ID so that one of them stays.
+fn undominated(
+    pools: &mut Vec<Arc<Pool>>,
+    directions: [(&Token, Option<(Decimal, Decimal)>); 2],
+) -> usize {
+    let scores: Vec<[Option<(Decimal, Decimal)>; 2]> = pools
+        .iter()
+        .map(|pool| directions.map(|(input, range)| bounds(pool, input, range)))
+        .collect();
+    let beats =
+        |x: usize, y: usize, direction: usize| match (scores[x][direction], scores[y][direction]) {
+            (Some(ours), Some(theirs)) => {
+                ours.0 >= theirs.0
+                    && ours.1 >= theirs.1
+                    && (ours != theirs || pools[x].id.0 < pools[y].id.0)
+            }
+            _ => false,
+        };
+    let dominated: Vec<bool> = (0..pools.len())
+        .map(|y| (0..2).all(|direction| (0..pools.len()).any(|x| x != y && beats(x, y, direction))))
+        .collect();
+
+    let (kept, dropped): (Vec<_>, Vec<_>) = pools
+        .drain(..)
+        .zip(dominated)
+        .partition(|(_, dominated)| !dominated);
+    let keep = kept.len();
+    pools.extend(kept.into_iter().chain(dropped).map(|(pool, _)| pool));
+    keep
+}
+
+/// Output of `pool` for `input` at the smallest and largest trade size, or the
+/// marginal price and output reserve without a size range.
+fn bounds(
+    pool: &Pool,
+    input: &Token,
+    range: Option<(Decimal, Decimal)>,
+) -> Option<(Decimal, Decimal)> {
+    match range {
+        Some((min, max)) if !min.is_zero() => Some((
+            pool.get_output_amount(input, min)?,
+            pool.get_output_amount(input, max)?,
+        )),
+        _ => {
+            let (reserve_in, reserve_out) = if *input == pool.token0 {
+                (pool.reserve0, pool.reserve1)
+            } else {
+                (pool.reserve1, pool.reserve0)
+            };
+            let fee_multiplier = Decimal::ONE - pool.fee_bps / Decimal::from(10_000);
+            let marginal = fee_multiplier
+                .checked_mul(reserve_out)?
+                .checked_div(reserve_in)?;
+            let high = match range {
+                Some((_, max)) => pool.get_output_amount(input, max)?,
+                None => reserve_out,
+            };
+            Some((marginal, high))
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::adapter::event_applier::{ApplyError, EventApplier};
+use crate::adapter::graph::{Graph, GraphError};
+use crate::domain::events::{BlockHash, BlockHeader, RawLog};
+use crate::domain::types::Pool;
+
+/// Number of blocks kept in the journal when not overridden.
+pub const DEFAULT_REORG_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReorgError {
+    Apply(ApplyError),
+    Graph(GraphError),
+    /// The block does not extend the current head; the caller must `reorg`.
+    NotChild {
+        head: BlockHash,
+        parent: BlockHash,
+    },
+    /// The common ancestor is older than anything still held in the journal.
+    UnknownAncestor(BlockHash),
+}
+
+impl Display for ReorgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReorgError::Apply(err) => write!(f, "{}", err),
+            ReorgError::Graph(err) => write!(f, "{}", err),
+            ReorgError::NotChild { head, parent } => write!(
+                f,
+                "block parent 0x{} is not the current head 0x{}",
+                hex::encode(parent),
+                hex::encode(head)
+            ),
+            ReorgError::UnknownAncestor(hash) => {
+                write!(f, "block 0x{} is not in the journal", hex::encode(hash))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReorgError {}
+
+impl From<ApplyError> for ReorgError {
+    fn from(err: ApplyError) -> Self {
+        ReorgError::Apply(err)
+    }
+}
+
+impl From<GraphError> for ReorgError {
+    fn from(err: GraphError) -> Self {
+        ReorgError::Graph(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    header: BlockHeader,
+    /// Pool states overwritten by the block, in the order they were changed.
+    undo: Vec<Arc<Pool>>,
+}
+
+/// Applies blocks of pair events to a `Graph` while keeping per-block undo journals.
+///
+/// When the chain reorganises, `reorg` rolls the graph back to the common
+/// ancestor by restoring the pool states each orphaned block overwrote, newest
+/// block first, then applies the canonical blocks. Only the last `max_depth`
+/// blocks can be undone. The journal operates on a `&mut Graph`, so it can run
+/// inside `GraphStore::update` to publish the result atomically.
+#[derive(Debug, Clone)]
+pub struct ReorgJournal {
+    applier: EventApplier,
+    entries: VecDeque<JournalEntry>,
+    max_depth: usize,
+}
+
+impl ReorgJournal {
+    pub fn new(applier: EventApplier) -> Self {
+        Self::with_max_depth(applier, DEFAULT_REORG_DEPTH)
+    }
+
+    pub fn with_max_depth(applier: EventApplier, max_depth: usize) -> Self {
+        Self {
+            applier,
+            entries: VecDeque::new(),
+            max_depth: max_depth.max(1),
+        }
+    }
+
+    /// The last applied block.
+    pub fn head(&self) -> Option<&BlockHeader> {
+        self.entries.back().map(|entry| &entry.header)
+    }
+
+    /// Applies the logs of `header`, which must be a child of the current head.
+    ///
+    /// A block is applied atomically: if any log fails, the changes already made
+    /// by that block are undone before the error is returned.
+    pub fn apply_block(
+        &mut self,
+        graph: &mut Graph,
+        header: BlockHeader,
+        logs: &[RawLog],
+    ) -> Result<(), ReorgError> {
+        if let Some(head) = self.head()
+            && head.hash != header.parent_hash
+        {
+            return Err(ReorgError::NotChild {
+                head: head.hash,
+                parent: header.parent_hash,
+            });
+        }
+
+        let mut undo = Vec::new();
+        for log in logs {
+            match self.applier.apply(graph, log) {
+                Ok(Some(previous)) => undo.push(previous),
+                Ok(None) => {}
+                Err(err) => {
+                    restore(graph, undo)?;
+                    return Err(err.into());
+                }
+            }
+        }
+
+        graph.block_number = header.number;
+        self.entries.push_back(JournalEntry { header, undo });
+        if self.entries.len() > self.max_depth {
+            self.entries.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Undoes every block above `ancestor` and returns how many were rolled back.
+    ///
+    /// `ancestor` may be any journaled block, or the parent of the oldest one.
+    pub fn rollback_to(
+        &mut self,
+        graph: &mut Graph,
+        ancestor: &BlockHash,
+    ) -> Result<usize, ReorgError> {
+        let keep = if let Some(index) = self
+            .entries
+            .iter()
+            .position(|entry| entry.header.hash == *ancestor)
+        {
+            index + 1
+        } else if self
+            .entries
+            .front()
+            .is_some_and(|oldest| oldest.header.parent_hash == *ancestor)
+        {
+            0
+        } else {
+            return Err(ReorgError::UnknownAncestor(*ancestor));
+        };
+
+        let mut rolled_back = 0;
+        while self.entries.len() > keep {
+            let entry = self.entries.pop_back().expect("length checked above");
+            restore(graph, entry.undo)?;
+            graph.block_number = entry.header.number.saturating_sub(1);
+            rolled_back += 1;
+        }
+        Ok(rolled_back)
+    }
+
+    /// Switches to a new canonical chain segment.
+    ///
+    /// `blocks` are ordered oldest first and the first one's parent must be in the
+    /// journal. Returns the number of orphaned blocks that were rolled back.
+    ///
+    /// The switch is atomic: the rollback and the new blocks are staged on copies
+    /// of the graph and the journal, which replace the originals only once every
+    /// block applied, so a failing block leaves both on the old chain.
+    pub fn reorg(
+        &mut self,
+        graph: &mut Graph,
+        blocks: &[(BlockHeader, Vec<RawLog>)],
+    ) -> Result<usize, ReorgError> {
+        let Some((first, _)) = blocks.first() else {
+            return Ok(0);
+        };
+        let mut staged_graph = graph.clone();
+        let mut staged = self.clone();
+        let rolled_back = staged.rollback_to(&mut staged_graph, &first.parent_hash)?;
+        for (header, logs) in blocks {
+            staged.apply_block(&mut staged_graph, *header, logs)?;
+        }
+        *graph = staged_graph;
+        *self = staged;
+        Ok(rolled_back)
+    }
+}
+
+fn restore(graph: &mut Graph, undo: Vec<Arc<Pool>>) -> Result<(), GraphError> {
+    for previous in undo.into_iter().rev() {
+        graph.replace_pool(previous)?;
+    }
+    Ok(())
+}
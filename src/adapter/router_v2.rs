@@ -0,0 +1,379 @@
+//! Calldata for the UniswapV2Router02 swap functions.
+
+use std::fmt::Display;
+
+use rust_decimal::dec;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::adapter::abi::{AbiValue, encode_call};
+use crate::adapter::graph::Graph;
+use crate::domain::types::{Address, ChainId, Route, Side, Slippage, Token, Venue};
+use crate::domain::venue::Venues;
+
+/// `bytes4(keccak256("swapExactTokensForTokens(uint256,uint256,address[],address,uint256)"))`
+pub const SWAP_EXACT_TOKENS_FOR_TOKENS: [u8; 4] = [0x38, 0xed, 0x17, 0x39];
+/// `bytes4(keccak256("swapTokensForExactTokens(uint256,uint256,address[],address,uint256)"))`
+pub const SWAP_TOKENS_FOR_EXACT_TOKENS: [u8; 4] = [0x88, 0x03, 0xdb, 0xee];
+/// `bytes4(keccak256("swapExactETHForTokens(uint256,address[],address,uint256)"))`
+pub const SWAP_EXACT_ETH_FOR_TOKENS: [u8; 4] = [0x7f, 0xf3, 0x6a, 0xb5];
+/// `bytes4(keccak256("swapTokensForExactETH(uint256,uint256,address[],address,uint256)"))`
+pub const SWAP_TOKENS_FOR_EXACT_ETH: [u8; 4] = [0x4a, 0x25, 0xd9, 0x4a];
+/// `bytes4(keccak256("swapExactTokensForETH(uint256,uint256,address[],address,uint256)"))`
+pub const SWAP_EXACT_TOKENS_FOR_ETH: [u8; 4] = [0x18, 0xcb, 0xaf, 0xe5];
+/// `bytes4(keccak256("swapETHForExactTokens(uint256,address[],address,uint256)"))`
+pub const SWAP_ETH_FOR_EXACT_TOKENS: [u8; 4] = [0xfb, 0x3b, 0xdb, 0x41];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalldataError {
+    EmptyRoute,
+    /// The graph has no address or decimals for a token of the path.
+    UnknownToken(Token),
+    /// An amount is negative or too large to encode in the token's units.
+    Amount {
+        token: Token,
+        amount: Decimal,
+    },
+    /// Routes of a split order do not all trade the same pair.
+    MismatchedLegs {
+        expected: (Token, Token),
+        actual: (Token, Token),
+    },
+    /// A router only swaps through the pairs of its own venue.
+    MixedVenues(Venue, Venue),
+    /// The venue has no configuration or is not supported by the router.
+    UnsupportedVenue(Venue),
+}
+
+impl Display for CalldataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalldataError::EmptyRoute => write!(f, "route has no steps"),
+            CalldataError::UnknownToken(token) => write!(f, "no address known for {}", token),
+            CalldataError::Amount { token, amount } => {
+                write!(f, "cannot encode {} {}", amount, token)
+            }
+            CalldataError::MismatchedLegs { expected, actual } => write!(
+                f,
+                "split route legs trade {} → {} and {} → {}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            CalldataError::MixedVenues(first, second) => {
+                write!(f, "route swaps on both {} and {}", first, second)
+            }
+            CalldataError::UnsupportedVenue(venue) => write!(f, "venue {} is not supported", venue),
+        }
+    }
+}
+
+impl std::error::Error for CalldataError {}
+
+/// A router swap with raw integer amounts.
+///
+/// For the payable variants the ETH sent with the call is part of the variant,
+/// see `value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouterCall {
+    SwapExactTokensForTokens {
+        amount_in: u128,
+        amount_out_min: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+    SwapTokensForExactTokens {
+        amount_out: u128,
+        amount_in_max: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+    SwapExactETHForTokens {
+        amount_in: u128,
+        amount_out_min: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+    SwapTokensForExactETH {
+        amount_out: u128,
+        amount_in_max: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+    SwapExactTokensForETH {
+        amount_in: u128,
+        amount_out_min: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+    SwapETHForExactTokens {
+        amount_out: u128,
+        amount_in_max: u128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    },
+}
+
+impl RouterCall {
+    pub fn selector(&self) -> [u8; 4] {
+        match self {
+            RouterCall::SwapExactTokensForTokens { .. } => SWAP_EXACT_TOKENS_FOR_TOKENS,
+            RouterCall::SwapTokensForExactTokens { .. } => SWAP_TOKENS_FOR_EXACT_TOKENS,
+            RouterCall::SwapExactETHForTokens { .. } => SWAP_EXACT_ETH_FOR_TOKENS,
+            RouterCall::SwapTokensForExactETH { .. } => SWAP_TOKENS_FOR_EXACT_ETH,
+            RouterCall::SwapExactTokensForETH { .. } => SWAP_EXACT_TOKENS_FOR_ETH,
+            RouterCall::SwapETHForExactTokens { .. } => SWAP_ETH_FOR_EXACT_TOKENS,
+        }
+    }
+
+    /// Wei to send along with the call.
+    pub fn value(&self) -> u128 {
+        match self {
+            RouterCall::SwapExactETHForTokens { amount_in, .. } => *amount_in,
+            RouterCall::SwapETHForExactTokens { amount_in_max, .. } => *amount_in_max,
+            _ => 0,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let tail = |path: &[Address], to: &Address, deadline: &u64| {
+            [
+                AbiValue::AddressArray(path.to_vec()),
+                AbiValue::Address(*to),
+                AbiValue::Uint(*deadline as u128),
+            ]
+        };
+        let args: Vec<AbiValue> = match self {
+            RouterCall::SwapExactTokensForTokens {
+                amount_in: first,
+                amount_out_min: second,
+                path,
+                to,
+                deadline,
+            }
+            | RouterCall::SwapExactTokensForETH {
+                amount_in: first,
+                amount_out_min: second,
+                path,
+                to,
+                deadline,
+            }
+            | RouterCall::SwapTokensForExactTokens {
+                amount_out: first,
+                amount_in_max: second,
+                path,
+                to,
+                deadline,
+            }
+            | RouterCall::SwapTokensForExactETH {
+                amount_out: first,
+                amount_in_max: second,
+                path,
+                to,
+                deadline,
+            } => [AbiValue::Uint(*first), AbiValue::Uint(*second)]
+                .into_iter()
+                .chain(tail(path, to, deadline))
+                .collect(),
+            // The amount in (or its maximum) travels as the call value
+            RouterCall::SwapExactETHForTokens {
+                amount_out_min: amount,
+                path,
+                to,
+                deadline,
+                ..
+            }
+            | RouterCall::SwapETHForExactTokens {
+                amount_out: amount,
+                path,
+                to,
+                deadline,
+                ..
+            } => std::iter::once(AbiValue::Uint(*amount))
+                .chain(tail(path, to, deadline))
+                .collect(),
+        };
+        encode_call(self.selector(), &args)
+    }
+}
+
+/// How a route is turned into a router call.
+#[derive(Debug, Clone)]
+pub struct SwapOptions {
+    pub recipient: Address,
+    /// Unix timestamp after which the swap reverts.
+    pub deadline: u64,
+    /// Bounds `amountOutMin` on sells and `amountInMax` on buys.
+    pub slippage: Slippage,
+    /// The wrapped native token (WETH); when a route starts or ends with it the
+    /// ETH variant of the swap is used.
+    pub native: Option<Token>,
+}
+
+/// Builds the router call for `route`, found for `amount_in` of its first token.
+///
+/// Every step must trade on the same venue, whose router the call is for, see
+/// `router_address`.
+///
+/// A sell spends exactly `amount_in` and requires the route output less the
+/// slippage tolerance. A buy receives exactly the route output and spends at
+/// most `amount_in` plus the tolerance. Token addresses and decimals come from
+/// the graph's token metadata.
+pub fn router_call(
+    graph: &Graph,
+    side: Side,
+    route: &Route,
+    amount_in: Decimal,
+    options: &SwapOptions,
+) -> Result<RouterCall, CalldataError> {
+    let (Some(first), Some(last)) = (route.steps.first(), route.steps.last()) else {
+        return Err(CalldataError::EmptyRoute);
+    };
+    route_venue(route)?;
+    let (token_in, token_out) = (&first.from, &last.to);
+    let path = std::iter::once(token_in)
+        .chain(route.steps.iter().map(|step| &step.to))
+        .map(|token| token_address(graph, token))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (to, deadline) = (options.recipient, options.deadline);
+    let tolerance = options.slippage.tolerance_bps / dec!(10000);
+    let eth_in = options.native.as_ref() == Some(token_in);
+    let eth_out = !eth_in && options.native.as_ref() == Some(token_out);
+
+    Ok(match side {
+        Side::Sell => {
+            let amount_in = raw_amount(graph, token_in, amount_in, RoundingStrategy::ToZero)?;
+            let amount_out_min = raw_amount(
+                graph,
+                token_out,
+                route.output_amount * (Decimal::ONE - tolerance),
+                RoundingStrategy::ToZero,
+            )?;
+            match (eth_in, eth_out) {
+                (true, _) => RouterCall::SwapExactETHForTokens {
+                    amount_in,
+                    amount_out_min,
+                    path,
+                    to,
+                    deadline,
+                },
+                (_, true) => RouterCall::SwapExactTokensForETH {
+                    amount_in,
+                    amount_out_min,
+                    path,
+                    to,
+                    deadline,
+                },
+                _ => RouterCall::SwapExactTokensForTokens {
+                    amount_in,
+                    amount_out_min,
+                    path,
+                    to,
+                    deadline,
+                },
+            }
+        }
+        Side::Buy => {
+            let amount_out = raw_amount(
+                graph,
+                token_out,
+                route.output_amount,
+                RoundingStrategy::ToZero,
+            )?;
+            let amount_in_max = raw_amount(
+                graph,
+                token_in,
+                amount_in * (Decimal::ONE + tolerance),
+                RoundingStrategy::AwayFromZero,
+            )?;
+            match (eth_in, eth_out) {
+                (true, _) => RouterCall::SwapETHForExactTokens {
+                    amount_out,
+                    amount_in_max,
+                    path,
+                    to,
+                    deadline,
+                },
+                (_, true) => RouterCall::SwapTokensForExactETH {
+                    amount_out,
+                    amount_in_max,
+                    path,
+                    to,
+                    deadline,
+                },
+                _ => RouterCall::SwapTokensForExactTokens {
+                    amount_out,
+                    amount_in_max,
+                    path,
+                    to,
+                    deadline,
+                },
+            }
+        }
+    })
+}
+
+/// Address of the router of the venue `route` trades on.
+pub fn router_address(
+    venues: &Venues,
+    chain_id: ChainId,
+    route: &Route,
+) -> Result<Address, CalldataError> {
+    let venue = route_venue(route)?;
+    venues
+        .get(chain_id, venue)
+        .map(|config| config.router)
+        .ok_or_else(|| CalldataError::UnsupportedVenue(venue.clone()))
+}
+
+/// The venue shared by every step of `route`.
+pub(crate) fn route_venue(route: &Route) -> Result<&Venue, CalldataError> {
+    let first = &route
+        .steps
+        .first()
+        .ok_or(CalldataError::EmptyRoute)?
+        .pool
+        .venue;
+    match route.steps.iter().find(|step| step.pool.venue != *first) {
+        Some(other) => Err(CalldataError::MixedVenues(
+            first.clone(),
+            other.pool.venue.clone(),
+        )),
+        None => Ok(first),
+    }
+}
+
+pub(crate) fn token_address(graph: &Graph, token: &Token) -> Result<Address, CalldataError> {
+    graph
+        .token_info(token)
+        .map(|info| info.address)
+        .ok_or_else(|| CalldataError::UnknownToken(token.clone()))
+}
+
+/// Converts `amount` of `token` into its smallest units, rounding with `strategy`.
+pub(crate) fn raw_amount(
+    graph: &Graph,
+    token: &Token,
+    amount: Decimal,
+    strategy: RoundingStrategy,
+) -> Result<u128, CalldataError> {
+    let info = graph
+        .token_info(token)
+        .ok_or_else(|| CalldataError::UnknownToken(token.clone()))?;
+    let invalid = || CalldataError::Amount {
+        token: token.clone(),
+        amount,
+    };
+    if amount.is_sign_negative() {
+        return Err(invalid());
+    }
+    let rounded = amount.round_dp_with_strategy(info.decimals, strategy);
+    10i128
+        .checked_pow(info.decimals - rounded.scale())
+        .and_then(|factor| rounded.mantissa().checked_mul(factor))
+        .map(|raw| raw as u128)
+        .ok_or_else(invalid)
+}
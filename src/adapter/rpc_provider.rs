@@ -0,0 +1,561 @@
+//! Pool state read from a node: `getReserves`, `factory`, `token0` and `token1`
+//! of each pair through batched JSON-RPC `eth_call`s.
+//!
+//! Only plain `http://` endpoints are supported; reach TLS endpoints through a
+//! local proxy.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_decimal::{Decimal, dec};
+use serde_json::{Value, json};
+
+use crate::adapter::event_applier::{DEFAULT_TOKEN_DECIMALS, scale};
+use crate::adapter::graph::{Graph, GraphError};
+use crate::domain::types::{Address, ChainId, Pool, PoolId, Token, TokenInfo, Venue};
+use crate::domain::venue::Venues;
+
+/// `bytes4(keccak256("getReserves()"))`
+pub const GET_RESERVES: [u8; 4] = [0x09, 0x02, 0xf1, 0xac];
+/// `bytes4(keccak256("token0()"))`
+pub const TOKEN0: [u8; 4] = [0x0d, 0xfe, 0x16, 0x81];
+/// `bytes4(keccak256("token1()"))`
+pub const TOKEN1: [u8; 4] = [0xd2, 0x12, 0x20, 0xa7];
+/// `bytes4(keccak256("factory()"))`
+pub const FACTORY: [u8; 4] = [0xc4, 0x5a, 0x01, 0x55];
+
+/// Calls sent per JSON-RPC batch unless configured otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 300;
+
+#[derive(Debug)]
+pub enum ProviderError {
+    Io(std::io::Error),
+    /// The endpoint answered with a non-success HTTP status.
+    Http(u16),
+    /// The endpoint URL cannot be used.
+    Endpoint(String),
+    /// The whole batch was rejected.
+    Rpc {
+        code: i64,
+        message: String,
+    },
+    /// The response is not what the request asked for.
+    Response(String),
+    /// A call on one pair failed or returned unexpected data.
+    Call {
+        pair: Address,
+        message: String,
+    },
+    /// The node serves another chain than the graph.
+    WrongChain {
+        expected: ChainId,
+        actual: ChainId,
+    },
+    Graph(GraphError),
+}
+
+impl Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderError::Io(err) => write!(f, "{}", err),
+            ProviderError::Http(status) => write!(f, "endpoint answered HTTP {}", status),
+            ProviderError::Endpoint(message) => write!(f, "invalid endpoint: {}", message),
+            ProviderError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            ProviderError::Response(message) => write!(f, "invalid RPC response: {}", message),
+            ProviderError::Call { pair, message } => {
+                write!(f, "call on {} failed: {}", pair, message)
+            }
+            ProviderError::WrongChain { expected, actual } => {
+                write!(
+                    f,
+                    "node is on chain {}, expected chain {}",
+                    actual, expected
+                )
+            }
+            ProviderError::Graph(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<std::io::Error> for ProviderError {
+    fn from(err: std::io::Error) -> Self {
+        ProviderError::Io(err)
+    }
+}
+
+impl From<GraphError> for ProviderError {
+    fn from(err: GraphError) -> Self {
+        ProviderError::Graph(err)
+    }
+}
+
+/// Sends a JSON-RPC request body and returns the response body.
+pub trait RpcTransport {
+    fn send(&self, body: &str) -> Result<String, ProviderError>;
+}
+
+/// JSON-RPC over HTTP/1.1, one connection per request.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl HttpTransport {
+    /// Parses an `http://host[:port][/path]` endpoint.
+    pub fn new(url: &str) -> Result<Self, ProviderError> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            return Err(ProviderError::Endpoint(format!(
+                "{} is not an http:// URL",
+                url
+            )));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| ProviderError::Endpoint(format!("invalid port in {}", url)))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(ProviderError::Endpoint(format!("no host in {}", url)));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl RpcTransport for HttpTransport {
+    fn send(&self, body: &str) -> Result<String, ProviderError> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            self.port,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+        read_response(BufReader::new(stream))
+    }
+}
+
+/// Reads a `Connection: close` response, sized by `Content-Length`, chunked, or
+/// running to the end of the stream.
+fn read_response(mut reader: impl BufRead) -> Result<String, ProviderError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status: u16 = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| ProviderError::Response(format!("bad status line {:?}", line.trim())))?;
+
+    let (mut length, mut chunked) = (None, false);
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse::<usize>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let size = line.trim().split(';').next().unwrap_or_default();
+            let size = usize::from_str_radix(size, 16)
+                .map_err(|_| ProviderError::Response(format!("bad chunk size {:?}", size)))?;
+            if size == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..])?;
+            line.clear();
+            reader.read_line(&mut line)?;
+        }
+    } else if let Some(length) = length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else {
+        reader.read_to_end(&mut body)?;
+    }
+
+    if !(200..300).contains(&status) {
+        return Err(ProviderError::Http(status));
+    }
+    String::from_utf8(body).map_err(|_| ProviderError::Response("body is not UTF-8".to_string()))
+}
+
+/// On-chain state of one pair, in raw token units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairState {
+    pub pair: Address,
+    /// Factory that deployed the pair, which identifies its venue.
+    pub factory: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub reserve0: u128,
+    pub reserve1: u128,
+}
+
+/// Reads pair state through a JSON-RPC endpoint and turns it into a `Graph`.
+pub struct PoolStateProvider<T> {
+    transport: T,
+    batch_size: usize,
+    venues: Venues,
+}
+
+impl<T: RpcTransport> PoolStateProvider<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            batch_size: DEFAULT_BATCH_SIZE,
+            venues: Venues::builtin(),
+        }
+    }
+
+    /// Venues recognised by their factory when loading pairs, `Venues::builtin` by default.
+    pub fn with_venues(mut self, venues: Venues) -> Self {
+        self.venues = venues;
+        self
+    }
+
+    /// Caps the number of calls per JSON-RPC batch, for nodes that limit batch sizes.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Fetches factory, tokens and reserves of every pair in `pairs` as of `block_number`.
+    pub fn fetch_pairs(
+        &self,
+        pairs: &[Address],
+        block_number: u64,
+    ) -> Result<Vec<PairState>, ProviderError> {
+        let calls: Vec<(Address, [u8; 4])> = pairs
+            .iter()
+            .flat_map(|&pair| {
+                [
+                    (pair, GET_RESERVES),
+                    (pair, FACTORY),
+                    (pair, TOKEN0),
+                    (pair, TOKEN1),
+                ]
+            })
+            .collect();
+        let results = self.eth_calls(&calls, block_number)?;
+        results
+            .chunks_exact(4)
+            .zip(pairs)
+            .map(|(words, &pair)| {
+                let [reserve0, reserve1] = reserves(pair, &words[0])?;
+                Ok(PairState {
+                    pair,
+                    factory: address_word(pair, &words[1])?,
+                    token0: address_word(pair, &words[2])?,
+                    token1: address_word(pair, &words[3])?,
+                    reserve0,
+                    reserve1,
+                })
+            })
+            .collect()
+    }
+
+    /// Current block number of the node.
+    pub fn block_number(&self) -> Result<u64, ProviderError> {
+        self.quantity("eth_blockNumber")
+    }
+
+    /// Chain the node is connected to.
+    pub fn chain_id(&self) -> Result<ChainId, ProviderError> {
+        self.quantity("eth_chainId")
+    }
+
+    /// Runtime bytecode deployed at `address` on the node's latest block, such
+    /// as a venue's router and pair code for `adapter::evm::EvmExecutor`.
+    pub fn code(&self, address: Address) -> Result<Vec<u8>, ProviderError> {
+        let response = self.request(&json!({
+            "jsonrpc": "2.0", "id": 0, "method": "eth_getCode",
+            "params": [address.to_string(), "latest"],
+        }))?;
+        let result = rpc_result(&response)?;
+        result
+            .as_str()
+            .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+            .ok_or_else(|| ProviderError::Response(format!("bad eth_getCode result {}", result)))
+    }
+
+    /// Calls a parameterless `method` returning a hex quantity.
+    fn quantity(&self, method: &str) -> Result<u64, ProviderError> {
+        let response = self.request(&json!({
+            "jsonrpc": "2.0", "id": 0, "method": method, "params": [],
+        }))?;
+        let result = rpc_result(&response)?;
+        result
+            .as_str()
+            .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| ProviderError::Response(format!("bad {} result {}", method, result)))
+    }
+
+    /// Builds a graph of `pairs` on the node's chain at its current block.
+    ///
+    /// Pools are identified by pair address. Their venue and fee come from the
+    /// configured venue with the pair's factory; pairs of other factories are put
+    /// on a venue named after the factory address, with the V2 fee of 30 bps.
+    /// Symbols and decimals come from the entries of `tokens` on the node's
+    /// chain; unknown tokens are named by address and assumed to have 18 decimals.
+    pub fn load_graph(
+        &self,
+        pairs: &[Address],
+        tokens: &[TokenInfo],
+    ) -> Result<Graph, ProviderError> {
+        let chain_id = self.chain_id()?;
+        let block_number = self.block_number()?;
+        let tokens: Vec<&TokenInfo> = tokens
+            .iter()
+            .filter(|info| info.chain_id == chain_id)
+            .collect();
+        let by_address: HashMap<Address, &TokenInfo> =
+            tokens.iter().map(|info| (info.address, *info)).collect();
+        let token = |address: &Address| match by_address.get(address) {
+            Some(info) => (info.symbol.clone(), info.decimals),
+            None => (Token::intern(&address.to_string()), DEFAULT_TOKEN_DECIMALS),
+        };
+
+        let mut graph = Graph::empty(chain_id);
+        for state in self.fetch_pairs(pairs, block_number)? {
+            let (token0, decimals0) = token(&state.token0);
+            let (token1, decimals1) = token(&state.token1);
+            let (venue, fee_bps) = match self.venues.by_factory(chain_id, &state.factory) {
+                Some(config) => (config.venue.clone(), config.fee_bps),
+                None => (Venue::intern(&state.factory.to_string()), dec!(30)),
+            };
+            graph.insert_pool(Arc::new(Pool {
+                id: PoolId::from_address(&state.pair),
+                token0,
+                token1,
+                reserve0: amount(state.pair, state.reserve0, decimals0)?,
+                reserve1: amount(state.pair, state.reserve1, decimals1)?,
+                fee_bps,
+                venue,
+                chain_id,
+            }))?;
+        }
+        let known = tokens
+            .into_iter()
+            .filter(|info| graph.adjacency.contains_key(&info.symbol))
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(graph
+            .with_token_info(known)?
+            .with_block_number(block_number))
+    }
+
+    /// Re-reads the reserves of every pool of `graph` whose ID is a pair
+    /// address and applies them, tagging the graph with the node's block.
+    ///
+    /// Fails without touching the graph when the node is on another chain.
+    /// Returns the number of pools refreshed.
+    pub fn refresh(&self, graph: &mut Graph) -> Result<usize, ProviderError> {
+        let chain_id = self.chain_id()?;
+        if chain_id != graph.chain_id {
+            return Err(ProviderError::WrongChain {
+                expected: graph.chain_id,
+                actual: chain_id,
+            });
+        }
+        let block_number = self.block_number()?;
+        let mut pools: Vec<(Address, Arc<Pool>)> = graph
+            .pools
+            .values()
+            .filter_map(|pool| Some((pool.id.0.parse().ok()?, Arc::clone(pool))))
+            .collect();
+        pools.sort_by_key(|(address, _)| *address);
+        let calls: Vec<(Address, [u8; 4])> = pools
+            .iter()
+            .map(|(address, _)| (*address, GET_RESERVES))
+            .collect();
+        let results = self.eth_calls(&calls, block_number)?;
+
+        let decimals = |token: &Token| {
+            graph
+                .token_info(token)
+                .map_or(DEFAULT_TOKEN_DECIMALS, |info| info.decimals)
+        };
+        let mut updates = Vec::with_capacity(pools.len());
+        for ((address, pool), word) in pools.iter().zip(&results) {
+            let [reserve0, reserve1] = reserves(*address, word)?;
+            updates.push((
+                pool.id.clone(),
+                amount(*address, reserve0, decimals(&pool.token0))?,
+                amount(*address, reserve1, decimals(&pool.token1))?,
+            ));
+        }
+        for (id, reserve0, reserve1) in &updates {
+            graph.update_reserves(id, *reserve0, *reserve1)?;
+        }
+        graph.block_number = block_number;
+        Ok(updates.len())
+    }
+
+    /// Runs `calls` as `eth_call`s at `block_number`, `batch_size` per request,
+    /// and returns their results in order.
+    ///
+    /// Pinning the block keeps every batch on the same state even when the node
+    /// imports a block between two of them.
+    fn eth_calls(
+        &self,
+        calls: &[(Address, [u8; 4])],
+        block_number: u64,
+    ) -> Result<Vec<Vec<u8>>, ProviderError> {
+        let block = format!("{:#x}", block_number);
+        let mut results = Vec::with_capacity(calls.len());
+        for batch in calls.chunks(self.batch_size) {
+            let requests: Vec<Value> = batch
+                .iter()
+                .enumerate()
+                .map(|(id, (to, selector))| {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "method": "eth_call",
+                        "params": [
+                            {"to": to.to_string(), "data": format!("0x{}", hex::encode(selector))},
+                            block,
+                        ],
+                    })
+                })
+                .collect();
+            let response = self.request(&Value::Array(requests))?;
+            let Value::Array(responses) = response else {
+                // Nodes answer a rejected batch with a single error object
+                rpc_result(&response)?;
+                return Err(ProviderError::Response(
+                    "expected a batch response".to_string(),
+                ));
+            };
+
+            // Batch responses may come back in any order
+            let mut by_id: Vec<Option<Vec<u8>>> = vec![None; batch.len()];
+            for response in &responses {
+                let id = response["id"]
+                    .as_u64()
+                    .map(|id| id as usize)
+                    .filter(|&id| id < batch.len())
+                    .ok_or_else(|| {
+                        ProviderError::Response(format!("unexpected id {}", response["id"]))
+                    })?;
+                let pair = batch[id].0;
+                let data = rpc_result(response)
+                    .map_err(|err| ProviderError::Call {
+                        pair,
+                        message: err.to_string(),
+                    })?
+                    .as_str()
+                    .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+                    .ok_or_else(|| ProviderError::Call {
+                        pair,
+                        message: "result is not hex data".to_string(),
+                    })?;
+                by_id[id] = Some(data);
+            }
+            for (data, (pair, _)) in by_id.into_iter().zip(batch) {
+                results.push(data.ok_or_else(|| ProviderError::Call {
+                    pair: *pair,
+                    message: "missing from the batch response".to_string(),
+                })?);
+            }
+        }
+        Ok(results)
+    }
+
+    fn request(&self, body: &Value) -> Result<Value, ProviderError> {
+        let response = self.transport.send(&body.to_string())?;
+        serde_json::from_str(&response).map_err(|err| ProviderError::Response(err.to_string()))
+    }
+}
+
+/// The `result` of a JSON-RPC response, or its `error`.
+fn rpc_result(response: &Value) -> Result<&Value, ProviderError> {
+    if let Some(error) = response.get("error") {
+        return Err(ProviderError::Rpc {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_string(),
+        });
+    }
+    response
+        .get("result")
+        .ok_or_else(|| ProviderError::Response("neither result nor error".to_string()))
+}
+
+/// Decodes `getReserves()`: `(uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)`.
+fn reserves(pair: Address, data: &[u8]) -> Result<[u128; 2], ProviderError> {
+    if data.len() != 96 {
+        return Err(ProviderError::Call {
+            pair,
+            message: format!("getReserves returned {} bytes", data.len()),
+        });
+    }
+    let word = |index: usize| {
+        let word = &data[index * 32..(index + 1) * 32];
+        u128::from_be_bytes(word[16..].try_into().expect("16 byte half word"))
+    };
+    Ok([word(0), word(1)])
+}
+
+/// Decodes an `address` return value.
+fn address_word(pair: Address, data: &[u8]) -> Result<Address, ProviderError> {
+    if data.len() != 32 || data[..12].iter().any(|&byte| byte != 0) {
+        return Err(ProviderError::Call {
+            pair,
+            message: format!("expected an address, got 0x{}", hex::encode(data)),
+        });
+    }
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&data[12..]);
+    Ok(Address(address))
+}
+
+fn amount(pair: Address, raw: u128, decimals: u32) -> Result<Decimal, ProviderError> {
+    scale(raw, decimals).ok_or_else(|| ProviderError::Call {
+        pair,
+        message: format!("reserve {} does not fit with {} decimals", raw, decimals),
+    })
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::domain::types::{
+    Address, LiquidityThresholds, MinTvl, Pool, PoolId, Token, TokenInfo, Venue,
+};
+
+/// Leading bytes of every snapshot file.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"UVGS";
+/// Format written by this build; snapshots with any other version are rejected.
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+const HEADER_LEN: usize = SNAPSHOT_MAGIC.len() + 4;
+const CHECKSUM_LEN: usize = 8;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    /// The data does not start with `SNAPSHOT_MAGIC`.
+    NotASnapshot,
+    UnsupportedVersion(u32),
+    ChecksumMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    /// The data ends in the middle of a field.
+    Truncated,
+    /// The checksum matched but the content is inconsistent.
+    Corrupt(String),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "snapshot i/o error: {}", err),
+            SnapshotError::NotASnapshot => write!(f, "not a graph snapshot"),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot format version {} is not supported (expected {})",
+                version, SNAPSHOT_VERSION
+            ),
+            SnapshotError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "snapshot checksum mismatch: expected {:016x}, got {:016x}",
+                expected, actual
+            ),
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::Corrupt(message) => write!(f, "corrupt snapshot: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+/// Encodes `graph` in the binary snapshot format.
+///
+/// Layout, little endian throughout:
+///
+/// ```text
+/// magic "UVGS" | version u32 | block_number u64 | chain_id u64
+/// liquidity: has_min_reserve u8, and if set min_reserve; has_min_tvl u8, and if
+///            set numeraire string, min_tvl value
+/// tokens:    count u32, then per token: symbol string, has_info u8, and if set
+///            chain_id u64, address [20], decimals u32, tag count u32, tag strings
+/// pools:     count u32, then per pool: id string, token0 u32, token1 u32,
+///            reserve0, reserve1, fee_bps (16 byte decimals), venue string
+/// adjacency: per token, in token order: count u32, then pool indices u32
+/// checksum   u64 FNV-1a over every preceding byte
+/// ```
+///
+/// Strings are a u32 byte length followed by UTF-8, decimals the 16 bytes of
+/// `Decimal::serialize`. Pools failing the liquidity thresholds are not stored
+/// but pruned again on load. Tokens known only from their
+/// metadata have an empty adjacency list. Tokens and pools are sorted so
+/// that equal graphs produce identical bytes; adjacency lists keep their order so
+/// that searches on the loaded graph visit pools exactly as before.
+pub fn encode(graph: &Graph) -> Vec<u8> {
+    let mut tokens: Vec<&Token> = graph
+        .adjacency
+        .keys()
+        .chain(graph.token_info.keys())
+        .collect();
+    tokens.sort_by_key(|token| token.0);
+    tokens.dedup();
+    let token_index: HashMap<&Token, u32> = tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| (*token, index as u32))
+        .collect();
+
+    let mut pools: Vec<&Arc<Pool>> = graph.pools.values().collect();
+    pools.sort_by_key(|pool| pool.id.0);
+    let pool_index: HashMap<&PoolId, u32> = pools
+        .iter()
+        .enumerate()
+        .map(|(index, pool)| (&pool.id, index as u32))
+        .collect();
+
+    let mut out = Vec::with_capacity(HEADER_LEN + 16 + pools.len() * 96);
+    out.extend_from_slice(&SNAPSHOT_MAGIC);
+    out.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    out.extend_from_slice(&graph.block_number.to_le_bytes());
+    out.extend_from_slice(&graph.chain_id.to_le_bytes());
+
+    match graph.liquidity.min_reserve {
+        Some(min_reserve) => {
+            out.push(1);
+            out.extend_from_slice(&min_reserve.serialize());
+        }
+        None => out.push(0),
+    }
+    match &graph.liquidity.min_tvl {
+        Some(min_tvl) => {
+            out.push(1);
+            put_str(&mut out, min_tvl.numeraire.0);
+            out.extend_from_slice(&min_tvl.value.serialize());
+        }
+        None => out.push(0),
+    }
+
+    put_u32(&mut out, tokens.len() as u32);
+    for token in &tokens {
+        put_str(&mut out, token.0);
+        match graph.token_info(token) {
+            Some(info) => {
+                out.push(1);
+                out.extend_from_slice(&info.chain_id.to_le_bytes());
+                out.extend_from_slice(&info.address.0);
+                put_u32(&mut out, info.decimals);
+                put_u32(&mut out, info.tags.len() as u32);
+                for tag in &info.tags {
+                    put_str(&mut out, tag);
+                }
+            }
+            None => out.push(0),
+        }
+    }
+
+    put_u32(&mut out, pools.len() as u32);
+    for pool in &pools {
+        put_str(&mut out, pool.id.0);
+        put_u32(&mut out, token_index[&pool.token0]);
+        put_u32(&mut out, token_index[&pool.token1]);
+        for amount in [pool.reserve0, pool.reserve1, pool.fee_bps] {
+            out.extend_from_slice(&amount.serialize());
+        }
+        put_str(&mut out, pool.venue.0);
+    }
+
+    for token in &tokens {
+        let adjacent = graph.adjacency.get(*token).map_or(&[][..], Vec::as_slice);
+        put_u32(&mut out, adjacent.len() as u32);
+        for pool in adjacent {
+            put_u32(&mut out, pool_index[&pool.id]);
+        }
+    }
+
+    let checksum = fnv1a(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+/// Decodes a snapshot produced by `encode`.
+///
+/// The magic, version and checksum are verified before anything else is read.
+pub fn decode(bytes: &[u8]) -> Result<Graph, SnapshotError> {
+    if bytes.len() < SNAPSHOT_MAGIC.len() || bytes[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::NotASnapshot);
+    }
+    if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+        return Err(SnapshotError::Truncated);
+    }
+    let version = u32::from_le_bytes(bytes[4..8].try_into().expect("4 byte version"));
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    let (body, trailer) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    let expected = u64::from_le_bytes(trailer.try_into().expect("8 byte checksum"));
+    let actual = fnv1a(body);
+    if expected != actual {
+        return Err(SnapshotError::ChecksumMismatch { expected, actual });
+    }
+
+    let mut reader = Reader {
+        bytes: &body[HEADER_LEN..],
+    };
+    let block_number = reader.u64()?;
+    let chain_id = reader.u64()?;
+    let min_reserve = match reader.flag("min reserve")? {
+        true => Some(reader.decimal()?),
+        false => None,
+    };
+    let min_tvl = match reader.flag("min TVL")? {
+        true => Some(MinTvl {
+            numeraire: Token::intern(reader.str()?),
+            value: reader.decimal()?,
+        }),
+        false => None,
+    };
+
+    let token_count = reader.u32()? as usize;
+    let mut tokens = Vec::with_capacity(token_count.min(reader.remaining()));
+    let mut token_info = HashMap::new();
+    for _ in 0..token_count {
+        let token = Token::intern(reader.str()?);
+        if reader.flag("token metadata")? {
+            let chain_id = reader.u64()?;
+            let address = Address(reader.take()?);
+            let decimals = reader.u32()?;
+            let tag_count = reader.u32()? as usize;
+            let mut tags = Vec::with_capacity(tag_count.min(reader.remaining()));
+            for _ in 0..tag_count {
+                tags.push(reader.str()?.to_string());
+            }
+            let info = TokenInfo {
+                chain_id,
+                address,
+                symbol: token.clone(),
+                decimals,
+                tags,
+            };
+            token_info.insert(token.clone(), info);
+        }
+        tokens.push(token);
+    }
+    let token_at = |index: u32| {
+        tokens
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| SnapshotError::Corrupt(format!("token index {} out of range", index)))
+    };
+
+    let pool_count = reader.u32()? as usize;
+    let mut pools = Vec::with_capacity(pool_count.min(reader.remaining()));
+    for _ in 0..pool_count {
+        let id = PoolId::intern(reader.str()?);
+        let token0 = token_at(reader.u32()?)?;
+        let token1 = token_at(reader.u32()?)?;
+        pools.push(Arc::new(Pool {
+            id,
+            token0,
+            token1,
+            reserve0: reader.decimal()?,
+            reserve1: reader.decimal()?,
+            fee_bps: reader.decimal()?,
+            venue: Venue::intern(reader.str()?),
+            chain_id,
+        }));
+    }
+
+    let mut adjacency = HashMap::with_capacity(tokens.len());
+    for token in &tokens {
+        let count = reader.u32()? as usize;
+        let mut adjacent = Vec::with_capacity(count.min(reader.remaining()));
+        for _ in 0..count {
+            let index = reader.u32()?;
+            let pool = pools.get(index as usize).ok_or_else(|| {
+                SnapshotError::Corrupt(format!("pool index {} out of range", index))
+            })?;
+            if pool.token0 != *token && pool.token1 != *token {
+                return Err(SnapshotError::Corrupt(format!(
+                    "pool {} listed under unrelated token {}",
+                    pool.id, token
+                )));
+            }
+            adjacent.push(Arc::clone(pool));
+        }
+        if !adjacent.is_empty() {
+            adjacency.insert(token.clone(), adjacent);
+        }
+    }
+    if reader.remaining() != 0 {
+        return Err(SnapshotError::Corrupt(format!(
+            "{} trailing bytes",
+            reader.remaining()
+        )));
+    }
+
+    // Every pool carries the header's chain, which an empty graph needs to be told
+    let mut graph = match pools.is_empty() {
+        true => Graph::empty(chain_id),
+        false => Graph::try_new(&pools).map_err(|err| SnapshotError::Corrupt(err.to_string()))?,
+    };
+    // The stored lists only decide the order pools are visited in
+    let ids = |pools: &[Arc<Pool>]| {
+        let mut ids: Vec<&str> = pools.iter().map(|pool| pool.id.0).collect();
+        ids.sort_unstable();
+        ids
+    };
+    if adjacency.len() != graph.adjacency.len()
+        || graph.adjacency.iter().any(|(token, built)| {
+            adjacency
+                .get(token)
+                .is_none_or(|stored: &Vec<Arc<Pool>>| ids(stored) != ids(built))
+        })
+    {
+        return Err(SnapshotError::Corrupt(
+            "adjacency lists do not match the pools".to_string(),
+        ));
+    }
+    graph.adjacency = adjacency;
+    graph.token_info = token_info;
+    Ok(graph
+        .with_block_number(block_number)
+        .with_liquidity(LiquidityThresholds {
+            min_reserve,
+            min_tvl,
+        }))
+}
+
+/// Writes a snapshot of `graph` to `path`.
+///
+/// The file is written next to `path` and renamed into place, so readers never
+/// see a half-written snapshot.
+pub fn save_snapshot(graph: &Graph, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = Path::new(&tmp_name);
+
+    let mut file = std::fs::File::create(tmp)?;
+    file.write_all(&encode(graph))?;
+    file.sync_all()?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// Reads the whole snapshot at `path` into memory and decodes it.
+pub fn load_snapshot(path: impl AsRef<Path>) -> Result<Graph, SnapshotError> {
+    decode(&std::fs::read(path)?)
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// 64-bit FNV-1a; enough to catch truncation and bit rot, not tampering.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        let bytes = self.slice(N)?;
+        Ok(bytes.try_into().expect("slice of requested length"))
+    }
+
+    fn slice(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() < len {
+            return Err(SnapshotError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn flag(&mut self, what: &str) -> Result<bool, SnapshotError> {
+        match self.take::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [flag] => Err(SnapshotError::Corrupt(format!(
+                "invalid {} flag {}",
+                what, flag
+            ))),
+        }
+    }
+
+    /// A decimal whose flags `Decimal::serialize` could have written: unused bits
+    /// clear and a scale of at most 28. `Decimal::deserialize` would otherwise
+    /// silently mask or rescale them.
+    fn decimal(&mut self) -> Result<Decimal, SnapshotError> {
+        let bytes = self.take::<16>()?;
+        if bytes[0] != 0 || bytes[1] != 0 || bytes[3] & 0x7f != 0 {
+            return Err(SnapshotError::Corrupt(format!(
+                "invalid decimal flags {:02x?}",
+                &bytes[..4]
+            )));
+        }
+        if u32::from(bytes[2]) > Decimal::MAX_SCALE {
+            return Err(SnapshotError::Corrupt(format!(
+                "decimal scale {} above {}",
+                bytes[2],
+                Decimal::MAX_SCALE
+            )));
+        }
+        Ok(Decimal::deserialize(bytes))
+    }
+
+    fn str(&mut self) -> Result<&'a str, SnapshotError> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.slice(len)?)
+            .map_err(|_| SnapshotError::Corrupt("string is not valid UTF-8".to_string()))
+    }
+}
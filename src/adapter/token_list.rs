@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::domain::types::{Address, InvalidAddress, Token, TokenInfo};
+
+#[derive(Debug)]
+pub enum TokenListError {
+    Io(std::io::Error),
+    /// The document is not valid JSON or lacks the `tokens` array.
+    Json(serde_json::Error),
+    /// Entry `index` of the `tokens` array does not follow the schema.
+    Token {
+        index: usize,
+        message: String,
+    },
+}
+
+impl Display for TokenListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenListError::Io(err) => write!(f, "cannot read token list: {}", err),
+            TokenListError::Json(err) => write!(f, "invalid token list: {}", err),
+            TokenListError::Token { index, message } => {
+                write!(f, "token #{}: {}", index, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenListError {}
+
+impl From<std::io::Error> for TokenListError {
+    fn from(err: std::io::Error) -> Self {
+        TokenListError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for TokenListError {
+    fn from(err: serde_json::Error) -> Self {
+        TokenListError::Json(err)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenListRecord {
+    #[serde(default)]
+    name: String,
+    tokens: Vec<serde_json::Value>,
+}
+
+/// One entry of the `tokens` array; `name`, `logoURI` and `extensions` are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenRecord {
+    chain_id: u64,
+    address: String,
+    symbol: String,
+    decimals: u8,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Tokens read from one or more token lists in the Uniswap token-list format.
+///
+/// Tokens are identified by chain and address; the same symbol may appear on
+/// several chains.
+#[derive(Debug, Clone, Default)]
+pub struct TokenList {
+    pub name: String,
+    pub tokens: Vec<TokenInfo>,
+    index: HashMap<(u64, Address), usize>,
+}
+
+impl TokenList {
+    /// Adds the tokens of `other` that are not in this list yet.
+    ///
+    /// When both lists have the same token, this list's symbol and decimals win and
+    /// the tags of both are kept.
+    pub fn merge(&mut self, other: TokenList) {
+        for info in other.tokens {
+            self.insert(info);
+        }
+    }
+
+    /// Merges `lists` in order of precedence, highest first.
+    pub fn merge_all(lists: impl IntoIterator<Item = TokenList>) -> TokenList {
+        let mut merged = TokenList::default();
+        for list in lists {
+            if merged.name.is_empty() {
+                merged.name = list.name.clone();
+            }
+            merged.merge(list);
+        }
+        merged
+    }
+
+    /// Tokens of the list deployed on `chain_id`.
+    pub fn for_chain(&self, chain_id: u64) -> impl Iterator<Item = &TokenInfo> {
+        self.tokens
+            .iter()
+            .filter(move |info| info.chain_id == chain_id)
+    }
+
+    /// Addresses of the tokens on `chain_id`, for use as `ExecutionParams::allowed_addresses`.
+    pub fn allow_list(&self, chain_id: u64) -> HashSet<Address> {
+        self.for_chain(chain_id).map(|info| info.address).collect()
+    }
+
+    fn insert(&mut self, info: TokenInfo) {
+        let key = (info.chain_id, info.address);
+        match self.index.get(&key) {
+            Some(&position) => {
+                let existing = &mut self.tokens[position];
+                for tag in info.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+            }
+            None => {
+                self.index.insert(key, self.tokens.len());
+                self.tokens.push(info);
+            }
+        }
+    }
+}
+
+/// Parses a token list:
+///
+/// ```json
+/// {"name": "Default", "tokens": [{"chainId": 1, "address": "0xa0b8…", "symbol": "USDC",
+///   "name": "USD Coin", "decimals": 6, "tags": ["stablecoin"]}]}
+/// ```
+///
+/// Duplicate entries for the same chain and address are merged.
+pub fn parse_token_list(input: &str) -> Result<TokenList, TokenListError> {
+    let record: TokenListRecord = serde_json::from_str(input)?;
+    let mut list = TokenList {
+        name: record.name,
+        ..Default::default()
+    };
+
+    for (index, entry) in record.tokens.into_iter().enumerate() {
+        let token_error = |message: String| TokenListError::Token { index, message };
+        let token = TokenRecord::deserialize(entry).map_err(|err| token_error(err.to_string()))?;
+        let address = token
+            .address
+            .parse()
+            .map_err(|err: InvalidAddress| token_error(err.to_string()))?;
+        if token.symbol.trim().is_empty() {
+            return Err(token_error("empty symbol".to_string()));
+        }
+        list.insert(TokenInfo {
+            chain_id: token.chain_id,
+            address,
+            symbol: Token::intern(&token.symbol),
+            decimals: u32::from(token.decimals),
+            tags: token.tags,
+        });
+    }
+    Ok(list)
+}
+
+pub fn load_token_list(path: impl AsRef<Path>) -> Result<TokenList, TokenListError> {
+    parse_token_list(&std::fs::read_to_string(path)?)
+}
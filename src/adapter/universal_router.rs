@@ -0,0 +1,287 @@
+//! Calldata for the Uniswap Universal Router `execute(bytes,bytes[],uint256)`.
+//!
+//! A route, or several routes splitting one order, becomes a list of commands
+//! run atomically in a single transaction.
+
+use rust_decimal::{Decimal, RoundingStrategy, dec};
+
+use crate::adapter::abi::{AbiValue, encode, encode_call};
+use crate::adapter::graph::Graph;
+use crate::adapter::router_v2::{
+    CalldataError, SwapOptions, raw_amount, route_venue, token_address,
+};
+use crate::domain::types::{Address, Route, Side, Token, Venue};
+
+/// `bytes4(keccak256("execute(bytes,bytes[],uint256)"))`
+pub const EXECUTE: [u8; 4] = [0x35, 0x93, 0x56, 0x4c];
+
+pub const SWEEP: u8 = 0x04;
+pub const V2_SWAP_EXACT_IN: u8 = 0x08;
+pub const V2_SWAP_EXACT_OUT: u8 = 0x09;
+pub const WRAP_ETH: u8 = 0x0b;
+pub const UNWRAP_WETH: u8 = 0x0c;
+
+/// Recipient placeholder for the caller of `execute`.
+pub const MSG_SENDER: Address =
+    Address([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+/// Recipient placeholder for the router itself, used between commands.
+pub const ADDRESS_THIS: Address =
+    Address([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    V2SwapExactIn {
+        recipient: Address,
+        amount_in: u128,
+        amount_out_min: u128,
+        path: Vec<Address>,
+        /// Pull the input from the caller rather than from the router's balance.
+        payer_is_user: bool,
+    },
+    V2SwapExactOut {
+        recipient: Address,
+        amount_out: u128,
+        amount_in_max: u128,
+        path: Vec<Address>,
+        payer_is_user: bool,
+    },
+    WrapEth {
+        recipient: Address,
+        amount_min: u128,
+    },
+    UnwrapWeth {
+        recipient: Address,
+        amount_min: u128,
+    },
+    Sweep {
+        token: Address,
+        recipient: Address,
+        amount_min: u128,
+    },
+}
+
+impl Command {
+    pub fn code(&self) -> u8 {
+        match self {
+            Command::V2SwapExactIn { .. } => V2_SWAP_EXACT_IN,
+            Command::V2SwapExactOut { .. } => V2_SWAP_EXACT_OUT,
+            Command::WrapEth { .. } => WRAP_ETH,
+            Command::UnwrapWeth { .. } => UNWRAP_WETH,
+            Command::Sweep { .. } => SWEEP,
+        }
+    }
+
+    /// The command's ABI-encoded input, as decoded by the router's dispatcher.
+    pub fn encode_input(&self) -> Vec<u8> {
+        match self {
+            Command::V2SwapExactIn {
+                recipient,
+                amount_in: first,
+                amount_out_min: second,
+                path,
+                payer_is_user,
+            }
+            | Command::V2SwapExactOut {
+                recipient,
+                amount_out: first,
+                amount_in_max: second,
+                path,
+                payer_is_user,
+            } => encode(&[
+                AbiValue::Address(*recipient),
+                AbiValue::Uint(*first),
+                AbiValue::Uint(*second),
+                AbiValue::AddressArray(path.clone()),
+                AbiValue::Bool(*payer_is_user),
+            ]),
+            Command::WrapEth {
+                recipient,
+                amount_min,
+            }
+            | Command::UnwrapWeth {
+                recipient,
+                amount_min,
+            } => encode(&[AbiValue::Address(*recipient), AbiValue::Uint(*amount_min)]),
+            Command::Sweep {
+                token,
+                recipient,
+                amount_min,
+            } => encode(&[
+                AbiValue::Address(*token),
+                AbiValue::Address(*recipient),
+                AbiValue::Uint(*amount_min),
+            ]),
+        }
+    }
+}
+
+/// An `execute` call and the wei to send with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecuteCall {
+    pub commands: Vec<Command>,
+    pub deadline: u64,
+    pub value: u128,
+}
+
+impl ExecuteCall {
+    pub fn encode(&self) -> Vec<u8> {
+        encode_call(
+            EXECUTE,
+            &[
+                AbiValue::Bytes(self.commands.iter().map(Command::code).collect()),
+                AbiValue::BytesArray(self.commands.iter().map(Command::encode_input).collect()),
+                AbiValue::Uint(self.deadline as u128),
+            ],
+        )
+    }
+}
+
+/// One route of an order and the part of the order's input it trades.
+#[derive(Debug, Clone)]
+pub struct RouteLeg<'a> {
+    pub route: &'a Route,
+    pub amount_in: Decimal,
+}
+
+/// Builds the `execute` call trading every leg of `legs`, which must share their
+/// first and last token.
+///
+/// Amounts follow `router_call`. The router's V2 commands only reach Uniswap
+/// pairs, so every pool must be on `Venue::UNISWAP`. With several legs, or when ETH is received,
+/// the output is collected by the router and the minimum is enforced once on
+/// the total by a final `SWEEP` or `UNWRAP_WETH`. When ETH is sent it is wrapped
+/// first, and unused ETH of a buy is refunded to the caller.
+pub fn execute_call(
+    graph: &Graph,
+    side: Side,
+    legs: &[RouteLeg],
+    options: &SwapOptions,
+) -> Result<ExecuteCall, CalldataError> {
+    let (token_in, token_out) = endpoints(legs)?;
+    for leg in legs {
+        let venue = route_venue(leg.route)?;
+        if *venue != Venue::UNISWAP {
+            return Err(CalldataError::UnsupportedVenue(venue.clone()));
+        }
+    }
+    let tolerance = options.slippage.tolerance_bps / dec!(10000);
+    let eth_in = options.native.as_ref() == Some(&token_in);
+    let eth_out = !eth_in && options.native.as_ref() == Some(&token_out);
+    // Output is held by the router when a final command has to forward it
+    let collect = eth_out || legs.len() > 1;
+    let leg_recipient = if collect {
+        ADDRESS_THIS
+    } else {
+        options.recipient
+    };
+
+    let mut commands = Vec::with_capacity(legs.len() + 2);
+    let mut total_in: u128 = 0;
+    let mut total_out: u128 = 0;
+    for leg in legs {
+        let path = std::iter::once(&token_in)
+            .chain(leg.route.steps.iter().map(|step| &step.to))
+            .map(|token| token_address(graph, token))
+            .collect::<Result<Vec<_>, _>>()?;
+        let command = match side {
+            Side::Sell => {
+                let amount_in =
+                    raw_amount(graph, &token_in, leg.amount_in, RoundingStrategy::ToZero)?;
+                let amount_out_min = raw_amount(
+                    graph,
+                    &token_out,
+                    leg.route.output_amount * (Decimal::ONE - tolerance),
+                    RoundingStrategy::ToZero,
+                )?;
+                total_in += amount_in;
+                total_out += amount_out_min;
+                Command::V2SwapExactIn {
+                    recipient: leg_recipient,
+                    amount_in,
+                    amount_out_min: if collect { 0 } else { amount_out_min },
+                    path,
+                    payer_is_user: !eth_in,
+                }
+            }
+            Side::Buy => {
+                let amount_out = raw_amount(
+                    graph,
+                    &token_out,
+                    leg.route.output_amount,
+                    RoundingStrategy::ToZero,
+                )?;
+                let amount_in_max = raw_amount(
+                    graph,
+                    &token_in,
+                    leg.amount_in * (Decimal::ONE + tolerance),
+                    RoundingStrategy::AwayFromZero,
+                )?;
+                total_in += amount_in_max;
+                total_out += amount_out;
+                Command::V2SwapExactOut {
+                    recipient: leg_recipient,
+                    amount_out,
+                    amount_in_max,
+                    path,
+                    payer_is_user: !eth_in,
+                }
+            }
+        };
+        commands.push(command);
+    }
+
+    if eth_in {
+        commands.insert(
+            0,
+            Command::WrapEth {
+                recipient: ADDRESS_THIS,
+                amount_min: total_in,
+            },
+        );
+    }
+    if eth_out {
+        commands.push(Command::UnwrapWeth {
+            recipient: options.recipient,
+            amount_min: total_out,
+        });
+    } else if collect {
+        commands.push(Command::Sweep {
+            token: token_address(graph, &token_out)?,
+            recipient: options.recipient,
+            amount_min: total_out,
+        });
+    }
+    if eth_in && side == Side::Buy {
+        commands.push(Command::UnwrapWeth {
+            recipient: MSG_SENDER,
+            amount_min: 0,
+        });
+    }
+
+    Ok(ExecuteCall {
+        commands,
+        deadline: options.deadline,
+        value: if eth_in { total_in } else { 0 },
+    })
+}
+
+/// First and last token shared by every leg.
+fn endpoints(legs: &[RouteLeg]) -> Result<(Token, Token), CalldataError> {
+    let mut found: Option<(Token, Token)> = None;
+    for leg in legs {
+        let (Some(first), Some(last)) = (leg.route.steps.first(), leg.route.steps.last()) else {
+            return Err(CalldataError::EmptyRoute);
+        };
+        match &found {
+            None => found = Some((first.from.clone(), last.to.clone())),
+            Some((from, to)) if *from == first.from && *to == last.to => {}
+            Some((from, to)) => {
+                return Err(CalldataError::MismatchedLegs {
+                    expected: (from.clone(), to.clone()),
+                    actual: (first.from.clone(), last.to.clone()),
+                });
+            }
+        }
+    }
+    found.ok_or(CalldataError::EmptyRoute)
+}
@@ -2,6 +2,7 @@ use rayon::iter::ParallelIterator;
 use rust_decimal::{Decimal, dec};
 
 use crate::adapter::graph::Graph;
+use crate::application::search_control::SearchControl;
 use crate::domain::types::{ExecutionParams, Route, Side, SwapStep, Token};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
@@ -47,12 +48,27 @@ impl Ord for AStarState {
 /// Returns:
 /// - An `Option<Route>` containing the best route if found, or `None` if no route exists.
 pub fn astar(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let mut control = SearchControl::new(params.budget);
+    astar_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`astar`], but stops once `control` reports the budget is exhausted,
+/// returning the best route that already reached `to`, flagged as partial.
+pub fn astar_with_control(
     _side: Side,
     graph: &Graph,
     from: &Token,
     to: &Token,
     amount_in: Decimal,
     params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<Route> {
     let mut heap = BinaryHeap::new();
     let mut initial_seen = HashSet::new();
@@ -67,6 +83,7 @@ pub fn astar(
     });
 
     let mut visited: HashMap<Token, Decimal> = HashMap::new();
+    let mut best_reached: Option<Route> = None;
 
     while let Some(AStarState {
         token,
@@ -76,23 +93,30 @@ pub fn astar(
         ..
     }) = heap.pop()
     {
+        if control.should_stop() {
+            return best_reached;
+        }
         if token == *to {
             return Some(Route {
                 steps: route,
                 output_amount: cumulative_amount,
+                partial: false,
             });
         }
         if route.len() >= params.max_hops {
             continue;
         }
-        if let Some(&seen_amt) = visited.get(&token) {
-            if seen_amt >= cumulative_amount {
-                continue;
-            }
+        if let Some(&seen_amt) = visited.get(&token)
+            && seen_amt >= cumulative_amount
+        {
+            continue;
         }
         visited.insert(token.clone(), cumulative_amount);
 
-        for (next_token, pool) in graph.neighbors(&token).collect::<Vec<_>>() {
+        for (next_token, pool) in graph
+            .routable_neighbors(&token, to, &params)
+            .collect::<Vec<_>>()
+        {
             if visited_tokens.contains(&next_token) {
                 continue;
             }
@@ -106,6 +130,13 @@ pub fn astar(
                 to: next_token.clone(),
                 pool: Arc::clone(&pool),
             });
+            if next_token == *to && best_reached.as_ref().is_none_or(|r| out > r.output_amount) {
+                best_reached = Some(Route {
+                    steps: new_route.clone(),
+                    output_amount: out,
+                    partial: true,
+                });
+            }
 
             let mut new_visited = visited_tokens.clone();
             new_visited.insert(next_token.clone());
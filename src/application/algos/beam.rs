@@ -0,0 +1,134 @@
+use rayon::iter::ParallelIterator;
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::application::search_control::SearchControl;
+use crate::domain::types::{ExecutionParams, Route, Side, SwapStep, Token};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+struct BeamState {
+    token: Token,
+    route: Vec<SwapStep>,
+    cumulative_amount: Decimal,
+}
+
+/// Beam search for finding the best route
+///
+/// Expands the frontier one hop at a time and, for every token reached,
+/// keeps only the `params.beam_width` partial routes with the highest
+/// cumulative amount. A width of 1 behaves like a greedy search, a large
+/// width approaches an exhaustive search bounded by `max_hops`.
+///
+/// Args:
+/// - `_side`: The side of the trade (not used in this implementation).
+/// - `graph`: The graph representing the pools.
+/// - `from`: The starting token.
+/// - `to`: The target token.
+/// - `amount_in`: The amount of the starting token.
+/// - `params`: Execution parameters, including max hops and beam width.
+///
+/// Returns:
+/// - An `Option<Route>` containing the best route if found, or `None` if no route exists.
+pub fn beam(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let mut control = SearchControl::new(params.budget);
+    beam_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`beam`], but stops once `control` reports the budget is exhausted,
+/// returning the best route found so far, flagged as partial.
+pub fn beam_with_control(
+    _side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+    control: &mut SearchControl,
+) -> Option<Route> {
+    let width = params.beam_width.max(1);
+    let mut frontier = vec![BeamState {
+        token: from.clone(),
+        route: vec![],
+        cumulative_amount: amount_in,
+    }];
+
+    let mut best_route: Option<Route> = None;
+
+    for _ in 0..params.max_hops {
+        let mut candidates: HashMap<Token, Vec<BeamState>> = HashMap::new();
+
+        for BeamState {
+            token,
+            route,
+            cumulative_amount,
+        } in frontier
+        {
+            if control.should_stop() {
+                return best_route.map(Route::into_partial);
+            }
+            for (next_token, pool) in graph
+                .routable_neighbors(&token, to, &params)
+                .collect::<Vec<_>>()
+            {
+                // Never revisit a token already on this partial route
+                if next_token == *from || route.iter().any(|step| step.to == next_token) {
+                    continue;
+                }
+                let Some(out) = pool.get_output_amount(&token, cumulative_amount) else {
+                    continue;
+                };
+                let mut new_route = route.clone();
+                new_route.push(SwapStep {
+                    from: token.clone(),
+                    to: next_token.clone(),
+                    pool: Arc::clone(&pool),
+                });
+
+                if next_token == *to {
+                    if best_route.as_ref().is_none_or(|r| out > r.output_amount) {
+                        best_route = Some(Route {
+                            steps: new_route,
+                            output_amount: out,
+                            partial: false,
+                        });
+                    }
+                    continue;
+                }
+
+                candidates
+                    .entry(next_token.clone())
+                    .or_default()
+                    .push(BeamState {
+                        token: next_token,
+                        route: new_route,
+                        cumulative_amount: out,
+                    });
+            }
+        }
+
+        frontier = candidates
+            .into_values()
+            .flat_map(|mut states| {
+                states.sort_by_key(|s| Reverse(s.cumulative_amount));
+                states.truncate(width);
+                states
+            })
+            .collect();
+
+        if frontier.is_empty() {
+            break;
+        }
+    }
+
+    best_route
+}
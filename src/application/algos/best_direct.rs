@@ -23,10 +23,10 @@ pub fn best_direct(
     from: &Token,
     to: &Token,
     amount_in: Decimal,
-    _params: ExecutionParams,
+    params: ExecutionParams,
 ) -> Option<Route> {
     graph
-        .neighbors(from)
+        .routable_neighbors(from, to, &params)
         .filter(|(t, _)| t == to)
         .filter_map(|(_, pool)| {
             pool.get_output_amount(from, amount_in).map(|out| Route {
@@ -36,6 +36,7 @@ pub fn best_direct(
                     pool: Arc::clone(&pool),
                 }],
                 output_amount: out,
+                partial: false,
             })
         })
         .max_by(|a, b| a.output_amount.cmp(&b.output_amount))
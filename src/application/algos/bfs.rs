@@ -2,6 +2,7 @@ use rayon::iter::ParallelIterator;
 use rust_decimal::Decimal;
 
 use crate::adapter::graph::Graph;
+use crate::application::search_control::SearchControl;
 use crate::domain::types::{ExecutionParams, Route, Side, SwapStep, Token};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
@@ -19,36 +20,59 @@ use std::sync::Arc;
 /// Returns:
 /// - An `Option<Route>` containing the best route if found, or `None` if no route exists.
 pub fn bfs(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let mut control = SearchControl::new(params.budget);
+    bfs_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`bfs`], but stops once `control` reports the budget is exhausted,
+/// returning the best route that already reached `to`, flagged as partial.
+pub fn bfs_with_control(
     _side: Side,
     graph: &Graph,
     from: &Token,
     to: &Token,
     amount_in: Decimal,
     params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<Route> {
     let mut queue = VecDeque::new();
     queue.push_back((from.clone(), vec![], amount_in));
 
     let mut visited: HashMap<Token, Decimal> = HashMap::new();
+    let mut best_reached: Option<Route> = None;
 
     while let Some((token, route, cumulative_amount)) = queue.pop_front() {
+        if control.should_stop() {
+            return best_reached;
+        }
         if token == *to {
             return Some(Route {
                 steps: route,
                 output_amount: cumulative_amount,
+                partial: false,
             });
         }
         if route.len() >= params.max_hops {
             continue;
         }
-        if let Some(&seen_amt) = visited.get(&token) {
-            if seen_amt >= cumulative_amount {
-                continue;
-            }
+        if let Some(&seen_amt) = visited.get(&token)
+            && seen_amt >= cumulative_amount
+        {
+            continue;
         }
         visited.insert(token.clone(), cumulative_amount);
 
-        for (next_token, pool) in graph.neighbors(&token).collect::<Vec<_>>() {
+        for (next_token, pool) in graph
+            .routable_neighbors(&token, to, &params)
+            .collect::<Vec<_>>()
+        {
             if let Some(out) = pool.get_output_amount(&token, cumulative_amount) {
                 let mut new_route = route.clone();
                 new_route.push(SwapStep {
@@ -56,6 +80,14 @@ pub fn bfs(
                     to: next_token.clone(),
                     pool: Arc::clone(&pool),
                 });
+                if next_token == *to && best_reached.as_ref().is_none_or(|r| out > r.output_amount)
+                {
+                    best_reached = Some(Route {
+                        steps: new_route.clone(),
+                        output_amount: out,
+                        partial: true,
+                    });
+                }
                 queue.push_back((next_token.clone(), new_route, out));
             }
         }
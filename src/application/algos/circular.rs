@@ -1,11 +1,12 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::apply_thresholds;
+use crate::application::search_control::{CancellationToken, SearchControl};
+use crate::domain::types::{ExecutionParams, Route, RouteOutcome, Side, SwapStep, Token};
 use rayon::iter::ParallelIterator;
 use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::sync::Arc;
-use crate::domain::types::{Side, Token, SwapStep, Route, ExecutionParams};
-use crate::adapter::graph::Graph;
-
 
 #[derive(Debug, Clone)]
 struct CircularState {
@@ -31,14 +32,63 @@ impl Ord for CircularState {
     }
 }
 
-
+/// Finds the most profitable cycle starting and ending at `from`.
+///
+/// Every cycle up to `params.max_hops` is explored and the one returning the
+/// largest amount of `from` wins; `to` is ignored.
 pub fn circular(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let (params, _) = apply_thresholds(graph, params);
+    let mut control = SearchControl::new(params.budget);
+    circular_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`circular`], but the search is abandoned as soon as `cancellation` is cancelled.
+///
+/// Cycles are searched the same way whatever the algorithm, so a found cycle is
+/// reported with `params.algo` unchanged.
+pub fn circular_cancellable(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+    cancellation: &CancellationToken,
+) -> RouteOutcome {
+    let (params, _) = apply_thresholds(graph, params);
+    let algo = params.algo;
+    let mut control = SearchControl::with_cancellation(params.budget, cancellation.clone());
+    let route = circular_with_control(side, graph, from, to, amount_in, params, &mut control);
+
+    if control.is_cancelled() {
+        return RouteOutcome::Cancelled;
+    }
+    match route {
+        Some(route) => RouteOutcome::Found(algo, route),
+        None => RouteOutcome::NoRoute,
+    }
+}
+
+/// Same as [`circular`], but stops once `control` reports the budget is exhausted,
+/// returning the best cycle found so far, flagged as partial.
+///
+/// Only the reserve threshold of `params.liquidity` is checked; pass parameters
+/// through `apply_thresholds` first for the TVL threshold to apply.
+pub fn circular_with_control(
     _side: Side,
     graph: &Graph,
     from: &Token,
     _to: &Token, // not used, as we want cycles starting and ending at `from`
     amount_in: Decimal,
     params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<Route> {
     let mut heap = BinaryHeap::new();
     heap.push(CircularState {
@@ -55,15 +105,19 @@ pub fn circular(
         cumulative_amount,
     }) = heap.pop()
     {
+        if control.should_stop() {
+            return best_route.map(Route::into_partial);
+        }
         // Only consider cycles that return to the starting token, and are not empty
         if token == *from && !route.is_empty() {
             let candidate = Route {
                 steps: route.clone(),
                 output_amount: cumulative_amount,
+                partial: false,
             };
             if best_route
                 .as_ref()
-                .map_or(true, |r| candidate.output_amount > r.output_amount)
+                .is_none_or(|r| candidate.output_amount > r.output_amount)
             {
                 best_route = Some(candidate);
             }
@@ -74,7 +128,10 @@ pub fn circular(
             continue;
         }
 
-        for (next_token, pool) in graph.neighbors(&token).collect::<Vec<_>>() {
+        for (next_token, pool) in graph
+            .routable_neighbors(&token, from, &params)
+            .collect::<Vec<_>>()
+        {
             // Prevent revisiting tokens in the same route (except for returning to `from`)
             if route.iter().any(|step| step.to == next_token) && next_token != *from {
                 continue;
@@ -2,6 +2,7 @@ use rayon::iter::ParallelIterator;
 use rust_decimal::Decimal;
 
 use crate::adapter::graph::Graph;
+use crate::application::search_control::SearchControl;
 use crate::domain::types::{ExecutionParams, Route, Side, SwapStep, Token};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
@@ -19,12 +20,27 @@ use std::sync::Arc;
 /// Returns:
 /// - An `Option<Route>` containing the best route if found, or `None` if no route exists.
 pub fn dfs(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let mut control = SearchControl::new(params.budget);
+    dfs_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`dfs`], but stops once `control` reports the budget is exhausted,
+/// returning the best route that already reached `to`, flagged as partial.
+pub fn dfs_with_control(
     _side: Side,
     graph: &Graph,
     from: &Token,
     to: &Token,
     amount_in: Decimal,
     params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<Route> {
     let mut stack = VecDeque::new();
     stack.push_back((
@@ -35,25 +51,33 @@ pub fn dfs(
     ));
 
     let mut visited: HashMap<Token, Decimal> = HashMap::new();
+    let mut best_reached: Option<Route> = None;
 
     while let Some((token, route, cumulative_amount, seen)) = stack.pop_back() {
+        if control.should_stop() {
+            return best_reached;
+        }
         if token == *to {
             return Some(Route {
                 steps: route,
                 output_amount: cumulative_amount,
+                partial: false,
             });
         }
         if route.len() >= params.max_hops {
             continue;
         }
-        if let Some(&seen_amt) = visited.get(&token) {
-            if seen_amt >= cumulative_amount {
-                continue;
-            }
+        if let Some(&seen_amt) = visited.get(&token)
+            && seen_amt >= cumulative_amount
+        {
+            continue;
         }
         visited.insert(token.clone(), cumulative_amount);
 
-        for (next_token, pool) in graph.neighbors(&token).collect::<Vec<_>>() {
+        for (next_token, pool) in graph
+            .routable_neighbors(&token, to, &params)
+            .collect::<Vec<_>>()
+        {
             if seen.contains(&next_token) {
                 continue;
             }
@@ -64,6 +88,14 @@ pub fn dfs(
                     to: next_token.clone(),
                     pool: Arc::clone(&pool),
                 });
+                if next_token == *to && best_reached.as_ref().is_none_or(|r| out > r.output_amount)
+                {
+                    best_reached = Some(Route {
+                        steps: new_route.clone(),
+                        output_amount: out,
+                        partial: true,
+                    });
+                }
                 let mut new_seen = seen.clone();
                 new_seen.insert(next_token.clone());
                 stack.push_back((next_token.clone(), new_route, out, new_seen));
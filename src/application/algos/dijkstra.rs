@@ -2,6 +2,7 @@ use rayon::iter::ParallelIterator;
 use rust_decimal::Decimal;
 
 use crate::adapter::graph::Graph;
+use crate::application::search_control::SearchControl;
 use crate::domain::types::{ExecutionParams, Route, Side, SwapStep, Token};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
@@ -44,12 +45,27 @@ impl Ord for DijkstraState {
 /// Returns:
 /// - An `Option<Route>` containing the best route if found, or `None` if no route exists.
 pub fn dijkstra(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+) -> Option<Route> {
+    let mut control = SearchControl::new(params.budget);
+    dijkstra_with_control(side, graph, from, to, amount_in, params, &mut control)
+}
+
+/// Same as [`dijkstra`], but stops once `control` reports the budget is exhausted,
+/// returning the best route that already reached `to`, flagged as partial.
+pub fn dijkstra_with_control(
     _side: Side,
     graph: &Graph,
     from: &Token,
     to: &Token,
     amount_in: Decimal,
     params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<Route> {
     let mut heap = BinaryHeap::new();
     heap.push(DijkstraState {
@@ -59,6 +75,7 @@ pub fn dijkstra(
     });
 
     let mut visited: HashMap<Token, Decimal> = HashMap::new();
+    let mut best_reached: Option<Route> = None;
 
     while let Some(DijkstraState {
         token,
@@ -66,23 +83,30 @@ pub fn dijkstra(
         cumulative_amount,
     }) = heap.pop()
     {
+        if control.should_stop() {
+            return best_reached;
+        }
         if token == *to {
             return Some(Route {
                 steps: route,
                 output_amount: cumulative_amount,
+                partial: false,
             });
         }
         if route.len() >= params.max_hops {
             continue;
         }
-        if let Some(&seen_amt) = visited.get(&token) {
-            if seen_amt >= cumulative_amount {
-                continue;
-            }
+        if let Some(&seen_amt) = visited.get(&token)
+            && seen_amt >= cumulative_amount
+        {
+            continue;
         }
         visited.insert(token.clone(), cumulative_amount);
 
-        for (next_token, pool) in graph.neighbors(&token).collect::<Vec<_>>() {
+        for (next_token, pool) in graph
+            .routable_neighbors(&token, to, &params)
+            .collect::<Vec<_>>()
+        {
             let Some(out) = pool.get_output_amount(&token, cumulative_amount) else {
                 continue;
             };
@@ -92,6 +116,13 @@ pub fn dijkstra(
                 to: next_token.clone(),
                 pool: Arc::clone(&pool),
             });
+            if next_token == *to && best_reached.as_ref().is_none_or(|r| out > r.output_amount) {
+                best_reached = Some(Route {
+                    steps: new_route.clone(),
+                    output_amount: out,
+                    partial: true,
+                });
+            }
 
             heap.push(DijkstraState {
                 token: next_token.clone(),
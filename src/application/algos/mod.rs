@@ -1,6 +1,7 @@
 pub mod astar;
+pub mod beam;
 pub mod best_direct;
 pub mod bfs;
+pub mod circular;
 pub mod dfs;
 pub mod dijkstra;
-pub mod circular;
\ No newline at end of file
@@ -1,2 +1,5 @@
 pub mod algos;
+pub mod quote;
 pub mod route_engine;
+pub mod search_control;
+pub mod verify;
@@ -0,0 +1,188 @@
+use rust_decimal::Decimal;
+use rust_decimal::dec;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::{PruneReport, apply_thresholds};
+use crate::application::algos::circular::circular_cancellable;
+use crate::application::search_control::CancellationToken;
+use crate::domain::types::{
+    Arbitrage, ArbitrageType, ChainId, ExecutionParams, Route, RouteOutcome, RoutingAlgo, Side,
+    Token,
+};
+use crate::port::routing::RoutingStrategy;
+
+/// A request for the best route between two tokens.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuoteRequest {
+    /// Chain to route on; mainnet when absent.
+    #[cfg_attr(feature = "serde", serde(default = "default_chain_id"))]
+    pub chain_id: ChainId,
+    #[cfg_attr(feature = "serde", serde(default = "default_side"))]
+    pub side: Side,
+    pub from: Token,
+    pub to: Token,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub amount_in: Decimal,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub params: ExecutionParams,
+}
+
+/// Largest amount a request may trade, in token units.
+pub const MAX_AMOUNT_IN: Decimal = dec!(1_000_000_000_000_000_000);
+
+impl QuoteRequest {
+    /// Checks that the amount is positive and at most `MAX_AMOUNT_IN`.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_amount(self.amount_in)
+    }
+}
+
+/// Checks that `amount` is positive and at most `MAX_AMOUNT_IN`.
+pub fn validate_amount(amount: Decimal) -> Result<(), String> {
+    if amount <= Decimal::ZERO {
+        return Err(format!("amount {} is not positive", amount));
+    }
+    if amount > MAX_AMOUNT_IN {
+        return Err(format!("amount {} exceeds {}", amount, MAX_AMOUNT_IN));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn default_chain_id() -> ChainId {
+    crate::domain::types::MAINNET
+}
+
+#[cfg(feature = "serde")]
+fn default_side() -> Side {
+    Side::Sell
+}
+
+/// The best route found for a `QuoteRequest`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Quote {
+    pub chain_id: ChainId,
+    pub side: Side,
+    pub from: Token,
+    pub to: Token,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub amount_in: Decimal,
+    pub algo: RoutingAlgo,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub output_amount: Decimal,
+    /// Output after deducting the request's slippage tolerance.
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub min_output_amount: Decimal,
+    pub route: Route,
+    /// Pools the request's liquidity thresholds kept out of the search.
+    pub pruned: PruneReport,
+}
+
+/// Computes the best route for `request` with `router`.
+///
+/// Returns `None` when the tokens are not connected within the request's limits,
+/// or when `graph` is not the graph of the request's chain.
+pub fn quote<R: RoutingStrategy + ?Sized>(
+    router: &R,
+    graph: &Graph,
+    request: &QuoteRequest,
+) -> Option<Quote> {
+    if graph.chain_id != request.chain_id {
+        return None;
+    }
+    let (params, pruned) = apply_thresholds(graph, request.params.clone());
+    let (algo, route) = router.compute_route(
+        request.side.clone(),
+        graph,
+        &request.from,
+        &request.to,
+        request.amount_in,
+        params,
+    )?;
+    let tolerance = request.params.slippage.tolerance_bps / dec!(10000);
+    Some(Quote {
+        chain_id: request.chain_id,
+        side: request.side.clone(),
+        from: request.from.clone(),
+        to: request.to.clone(),
+        amount_in: request.amount_in,
+        algo,
+        output_amount: route.output_amount,
+        min_output_amount: route.output_amount * (Decimal::ONE - tolerance),
+        route,
+        pruned,
+    })
+}
+
+/// A profitable cycle found by `scan_arbitrage`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Opportunity {
+    pub token: Token,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub amount_in: Decimal,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub profit: Decimal,
+    pub arbitrage: Arbitrage,
+}
+
+/// Looks for a cycle starting and ending at each of `tokens` that returns more than
+/// `min_profit` on top of `amount_in`.
+///
+/// Returns the opportunities sorted by decreasing profit.
+pub fn scan_arbitrage(
+    graph: &Graph,
+    tokens: &[Token],
+    amount_in: Decimal,
+    min_profit: Decimal,
+    params: &ExecutionParams,
+) -> Vec<Opportunity> {
+    let cancellation = CancellationToken::new();
+    scan_arbitrage_cancellable(graph, tokens, amount_in, min_profit, params, &cancellation)
+        .expect("the scan owns its token, so nothing cancels it")
+}
+
+/// Same as [`scan_arbitrage`], but gives up as soon as `cancellation` is cancelled,
+/// returning `None` instead of the opportunities of the tokens already scanned.
+pub fn scan_arbitrage_cancellable(
+    graph: &Graph,
+    tokens: &[Token],
+    amount_in: Decimal,
+    min_profit: Decimal,
+    params: &ExecutionParams,
+    cancellation: &CancellationToken,
+) -> Option<Vec<Opportunity>> {
+    let (params, _) = apply_thresholds(graph, params.clone());
+    let mut found = Vec::new();
+    for token in tokens {
+        let route = match circular_cancellable(
+            Side::Sell,
+            graph,
+            token,
+            token,
+            amount_in,
+            params.clone(),
+            cancellation,
+        ) {
+            RouteOutcome::Found(_, route) => route,
+            RouteOutcome::NoRoute => continue,
+            RouteOutcome::Cancelled => return None,
+        };
+        let profit = route.output_amount - amount_in;
+        if profit > min_profit {
+            found.push(Opportunity {
+                token: token.clone(),
+                amount_in,
+                profit,
+                arbitrage: Arbitrage {
+                    arbitrage_type: ArbitrageType::Circular,
+                    route,
+                },
+            });
+        }
+    }
+    found.sort_by_key(|opportunity| std::cmp::Reverse(opportunity.profit));
+    Some(found)
+}
@@ -3,10 +3,23 @@ use rayon::iter::ParallelIterator;
 use rust_decimal::Decimal;
 
 use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::apply_thresholds;
 use crate::application::algos::{
-    astar::astar, best_direct::best_direct, bfs::bfs, dfs::dfs, dijkstra::dijkstra,
+    astar::astar_with_control, beam::beam_with_control, best_direct::best_direct,
+    bfs::bfs_with_control, dfs::dfs_with_control, dijkstra::dijkstra_with_control,
 };
-use crate::domain::types::{ExecutionParams, Route, RoutingAlgo, Side, Token};
+use crate::application::search_control::{CancellationToken, SearchControl};
+use crate::domain::types::{ExecutionParams, Route, RouteOutcome, RoutingAlgo, Side, Token};
+
+/// Algorithms raced against each other by `RoutingAlgo::Auto`.
+const AUTO_CANDIDATES: [RoutingAlgo; 6] = [
+    RoutingAlgo::BestDirect,
+    RoutingAlgo::AStar,
+    RoutingAlgo::Dijkstra,
+    RoutingAlgo::Bfs,
+    RoutingAlgo::Dfs,
+    RoutingAlgo::Beam,
+];
 
 pub fn execute(
     side: Side,
@@ -15,22 +28,57 @@ pub fn execute(
     to: &Token,
     amount_in: Decimal,
     exec_params: ExecutionParams,
+) -> Option<(RoutingAlgo, Route)> {
+    let (exec_params, _) = apply_thresholds(graph, exec_params);
+    let mut control = SearchControl::new(exec_params.budget);
+    execute_with_control(side, graph, from, to, amount_in, exec_params, &mut control)
+}
+
+/// Same as [`execute`], but the search is abandoned as soon as `cancellation` is cancelled.
+pub fn execute_cancellable(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    exec_params: ExecutionParams,
+    cancellation: &CancellationToken,
+) -> RouteOutcome {
+    let (exec_params, _) = apply_thresholds(graph, exec_params);
+    let mut control = SearchControl::with_cancellation(exec_params.budget, cancellation.clone());
+    let route = execute_with_control(side, graph, from, to, amount_in, exec_params, &mut control);
+
+    if control.is_cancelled() {
+        return RouteOutcome::Cancelled;
+    }
+    match route {
+        Some((algo, route)) => RouteOutcome::Found(algo, route),
+        None => RouteOutcome::NoRoute,
+    }
+}
+
+fn execute_with_control(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    exec_params: ExecutionParams,
+    control: &mut SearchControl,
 ) -> Option<(RoutingAlgo, Route)> {
     match exec_params.algo {
-        RoutingAlgo::BestDirect => best_direct(side, graph, from, to, amount_in, exec_params)
-            .map(|route| (RoutingAlgo::BestDirect, route)),
-        RoutingAlgo::AStar => astar(side, graph, from, to, amount_in, exec_params)
-            .map(|route| (RoutingAlgo::AStar, route)),
-        RoutingAlgo::Dijkstra => dijkstra(side, graph, from, to, amount_in, exec_params)
-            .map(|route| (RoutingAlgo::Dijkstra, route)),
-        RoutingAlgo::Bfs => bfs(side, graph, from, to, amount_in, exec_params)
-            .map(|route| (RoutingAlgo::Bfs, route)),
-        RoutingAlgo::Dfs => dfs(side, graph, from, to, amount_in, exec_params)
-            .map(|route| (RoutingAlgo::Dfs, route)),
-        RoutingAlgo::Auto => select_best_route(side, graph, from, to, amount_in, exec_params),
+        RoutingAlgo::Auto => {
+            select_best_route_with_control(side, graph, from, to, amount_in, exec_params, control)
+        }
+        algo => run_algo(algo, side, graph, from, to, amount_in, exec_params, control)
+            .map(|route| (algo, route)),
     }
 }
 
+/// Runs every candidate algorithm in parallel and keeps the route with the highest output.
+///
+/// All candidates share the request's time budget. If any of them was cut short, the
+/// returned route is flagged as partial since a complete search might have done better.
 pub fn select_best_route(
     side: Side,
     graph: &Graph,
@@ -39,31 +87,76 @@ pub fn select_best_route(
     amount_in: Decimal,
     params: ExecutionParams,
 ) -> Option<(RoutingAlgo, Route)> {
-    let candidates = [
-        (
-            RoutingAlgo::BestDirect,
-            best_direct(side.clone(), graph, from, to, amount_in, params),
-        ),
-        (
-            RoutingAlgo::AStar,
-            astar(side.clone(), graph, from, to, amount_in, params),
-        ),
-        (
-            RoutingAlgo::Dijkstra,
-            dijkstra(side.clone(), graph, from, to, amount_in, params),
-        ),
-        (
-            RoutingAlgo::Bfs,
-            bfs(side.clone(), graph, from, to, amount_in, params),
-        ),
-        (
-            RoutingAlgo::Dfs,
-            dfs(side.clone(), graph, from, to, amount_in, params),
-        ),
-    ];
+    let (params, _) = apply_thresholds(graph, params);
+    let control = SearchControl::new(params.budget);
+    select_best_route_with_control(side, graph, from, to, amount_in, params, &control)
+}
 
-    candidates
+fn select_best_route_with_control(
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+    template: &SearchControl,
+) -> Option<(RoutingAlgo, Route)> {
+    let candidates: Vec<(RoutingAlgo, Option<Route>, bool)> = AUTO_CANDIDATES
         .into_par_iter()
-        .filter_map(|(algo, opt)| opt.map(|r| (algo, r)))
+        .map(|algo| {
+            let mut control = template.clone();
+            let route = run_algo(
+                algo,
+                side.clone(),
+                graph,
+                from,
+                to,
+                amount_in,
+                params.clone(),
+                &mut control,
+            );
+            (algo, route, control.is_exhausted())
+        })
+        .collect();
+
+    let partial = candidates.iter().any(|(_, _, exhausted)| *exhausted);
+
+    candidates
+        .into_iter()
+        .filter_map(|(algo, route, _)| route.map(|r| (algo, r)))
         .max_by(|a, b| a.1.output_amount.cmp(&b.1.output_amount))
+        .map(|(algo, route)| {
+            if partial {
+                (algo, route.into_partial())
+            } else {
+                (algo, route)
+            }
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_algo(
+    algo: RoutingAlgo,
+    side: Side,
+    graph: &Graph,
+    from: &Token,
+    to: &Token,
+    amount_in: Decimal,
+    params: ExecutionParams,
+    control: &mut SearchControl,
+) -> Option<Route> {
+    match algo {
+        RoutingAlgo::BestDirect => best_direct(side, graph, from, to, amount_in, params),
+        RoutingAlgo::AStar => astar_with_control(side, graph, from, to, amount_in, params, control),
+        RoutingAlgo::Dijkstra => {
+            dijkstra_with_control(side, graph, from, to, amount_in, params, control)
+        }
+        RoutingAlgo::Bfs => bfs_with_control(side, graph, from, to, amount_in, params, control),
+        RoutingAlgo::Dfs => dfs_with_control(side, graph, from, to, amount_in, params, control),
+        RoutingAlgo::Beam => beam_with_control(side, graph, from, to, amount_in, params, control),
+        RoutingAlgo::Auto => {
+            select_best_route_with_control(side, graph, from, to, amount_in, params, control)
+                .map(|(_, route)| route)
+        }
+    }
 }
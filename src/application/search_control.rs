@@ -0,0 +1,124 @@
+use std::sync::Arc;
+#[cfg(test)]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::domain::types::SearchBudget;
+
+/// Shared flag used to abandon in-flight searches, e.g. when a client disconnects
+/// or a new block makes the quote obsolete.
+///
+/// Clones share the same flag, so the token can be handed to a search and
+/// cancelled from any other thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// Checks left before the token cancels itself, for tests that cancel a
+    /// search at a known point.
+    #[cfg(test)]
+    countdown: Option<Arc<AtomicUsize>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that cancels itself on its `checks`-th `is_cancelled` call.
+    #[cfg(test)]
+    pub(crate) fn cancelled_on_check(checks: usize) -> Self {
+        Self {
+            countdown: Some(Arc::new(AtomicUsize::new(checks))),
+            ..Self::default()
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        #[cfg(test)]
+        if let Some(countdown) = &self.countdown
+            && countdown.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            }) == Ok(1)
+        {
+            self.cancel();
+        }
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Tracks the progress of a search against its `SearchBudget`.
+///
+/// Algorithms call `should_stop` once per node expansion and, when it returns
+/// `true`, stop and hand back the best route found so far. Cloning a control
+/// keeps its start time and cancellation token, which lets several algorithms
+/// share one deadline and be cancelled together.
+#[derive(Debug, Clone)]
+pub struct SearchControl {
+    budget: SearchBudget,
+    started: Instant,
+    expansions: usize,
+    exhausted: bool,
+    cancellation: Option<CancellationToken>,
+}
+
+impl SearchControl {
+    pub fn new(budget: SearchBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            expansions: 0,
+            exhausted: false,
+            cancellation: None,
+        }
+    }
+
+    /// Creates a control that also stops as soon as `cancellation` is cancelled.
+    pub fn with_cancellation(budget: SearchBudget, cancellation: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(cancellation),
+            ..Self::new(budget)
+        }
+    }
+
+    /// Records one expansion and returns `true` if the search must stop.
+    pub fn should_stop(&mut self) -> bool {
+        if self.exhausted || self.is_cancelled() {
+            return true;
+        }
+        self.expansions += 1;
+
+        let out_of_expansions = self
+            .budget
+            .max_expansions
+            .is_some_and(|max| self.expansions > max);
+        let out_of_time = self
+            .budget
+            .max_duration
+            .is_some_and(|max| self.started.elapsed() >= max);
+
+        self.exhausted = out_of_expansions || out_of_time;
+        self.exhausted
+    }
+
+    /// Returns `true` once the budget has been exceeded.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Returns `true` if the search was cancelled through its token.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Number of expansions recorded so far.
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+}
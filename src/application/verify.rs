@@ -0,0 +1,94 @@
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::domain::types::{PoolId, Route, Token};
+use crate::port::verification::{ExecutionError, RouteExecutor};
+
+/// One hop of a verified route.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HopCheck {
+    pub pool: PoolId,
+    pub to: Token,
+    /// Amount received according to the router's math.
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub expected: Decimal,
+    /// Amount received according to the executor.
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub executed: Decimal,
+}
+
+/// The router's quote for a route next to an independent execution of it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Verification {
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub expected: Decimal,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub executed: Decimal,
+    pub hops: Vec<HopCheck>,
+}
+
+impl Verification {
+    /// `expected - executed`; positive when the router promised more than executes.
+    pub fn discrepancy(&self) -> Decimal {
+        self.expected - self.executed
+    }
+
+    /// Discrepancy as a fraction of the expected output.
+    pub fn relative_discrepancy(&self) -> Decimal {
+        if self.expected.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.discrepancy().abs() / self.expected
+    }
+
+    /// `true` when the outputs differ by at most `tolerance`, relative to the expected output.
+    pub fn is_within(&self, tolerance: Decimal) -> bool {
+        self.relative_discrepancy() <= tolerance
+    }
+}
+
+/// Executes `route` for `amount_in` with `executor` and compares each hop with
+/// the router's own math on the same graph.
+///
+/// Args:
+///     executor: independent implementation of the swaps
+///     graph: pools whose reserves both sides use
+///     route: the route to check, usually from a quote
+///     amount_in: amount of the route's first token
+///
+/// Returns:
+///     The expected and executed amounts, overall and per hop
+pub fn verify_route(
+    executor: &dyn RouteExecutor,
+    graph: &Graph,
+    route: &Route,
+    amount_in: Decimal,
+) -> Result<Verification, ExecutionError> {
+    let executed = executor.execute(graph, route, amount_in)?;
+    let mut amount = amount_in;
+    let mut hops = Vec::with_capacity(route.steps.len());
+    for (step, executed) in route.steps.iter().zip(executed) {
+        let pool = graph
+            .pool(&step.pool.id)
+            .ok_or_else(|| ExecutionError::UnknownPool(step.pool.id.clone()))?;
+        amount =
+            pool.get_output_amount(&step.from, amount)
+                .ok_or_else(|| ExecutionError::Reverted {
+                    pool: pool.id.clone(),
+                    reason: format!("{} is not traded by the pool", step.from),
+                })?;
+        hops.push(HopCheck {
+            pool: pool.id.clone(),
+            to: step.to.clone(),
+            expected: amount,
+            executed,
+        });
+    }
+    Ok(Verification {
+        expected: route.output_amount,
+        executed: hops.last().map_or(amount_in, |hop| hop.executed),
+        hops,
+    })
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use uniswap_routing::adapter::graph_registry::GraphRegistry;
+use uniswap_routing::adapter::loader::{LoadError, parse_csv};
+use uniswap_routing::cli::load_graph_file;
+use uniswap_routing::infra::route_cache::CachedRouter;
+use uniswap_routing::port::routing::DefaultRouter;
+use uniswap_routing::server::service::QuoteService;
+use uniswap_routing::server::{QuoteServer, rpc};
+
+const USAGE: &str = "usage: uniswap-routing-server [--pools FILE]... [--addr HOST:PORT | --stdio]";
+
+fn main() -> ExitCode {
+    let mut pools: Vec<PathBuf> = Vec::new();
+    let mut addr = String::from("127.0.0.1:8080");
+    let mut stdio = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--stdio" {
+            stdio = true;
+            continue;
+        }
+        match (arg.as_str(), args.next()) {
+            ("--pools", Some(path)) => pools.push(path.into()),
+            ("--addr", Some(value)) => addr = value,
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    // One pool file per chain
+    let mut registry = GraphRegistry::new();
+    for path in &pools {
+        let graph = match load_graph_file(path) {
+            Ok(graph) => graph,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return ExitCode::from(err.exit_code());
+            }
+        };
+        if registry.store(graph.chain_id()).is_ok() {
+            eprintln!(
+                "error: {} holds pools of chain {}, already loaded",
+                path.display(),
+                graph.chain_id()
+            );
+            return ExitCode::from(3);
+        }
+        registry = registry.with_graph(graph);
+    }
+    if pools.is_empty() {
+        registry = registry.with_graph(
+            parse_csv(include_str!("../../data/demo_pools.csv"))
+                .and_then(|dump| dump.graph().map_err(LoadError::from))
+                .expect("bundled demo pools are valid"),
+        );
+    }
+
+    let service = QuoteService::with_registry(CachedRouter::new(DefaultRouter), registry);
+    if stdio {
+        return match rpc::serve(&service, std::io::stdin().lock(), std::io::stdout().lock()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let server = QuoteServer::new(service);
+    match server.spawn(&addr) {
+        Ok(handle) => {
+            println!("listening on http://{}", handle.local_addr());
+            handle.join();
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: cannot listen on {}: {}", addr, err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::parallel_pools::{ParallelPoolPolicy, TradeSizes};
+use crate::application::quote::validate_amount;
+use crate::cli::CliError;
+use crate::domain::types::{LiquidityThresholds, MinTvl, RoutingAlgo, Side, Token};
+
+pub const USAGE: &str = "\
+usage: uniswap-routing [--pools FILE] [--json] [--min-reserve AMOUNT]
+                       [--min-tvl AMOUNT --numeraire TOKEN]
+                       [--pools-per-pair N | --drop-dominated-pools] <command>
+
+commands:
+  quote <FROM> <TO> <AMOUNT> [--side sell] [--slippage BPS] [--max-hops N] [--algo ALGO]
+        [--verify]
+      best route for swapping AMOUNT of FROM into TO; --verify replays it with
+      a transcription of the pair contracts' integer math (not their bytecode)
+      and reports the discrepancy
+  arb [--token TOKEN] [--amount AMOUNT] [--max-hops N] [--min-profit AMOUNT]
+      profitable cycles starting from TOKEN, or from every token
+  graph stats
+      token, pool and connectivity counts of the loaded graph
+  bench [--iterations N] [--amount AMOUNT] [--max-hops N] [--algo ALGO]
+      time every algorithm over all token pairs
+
+options:
+  --pools FILE   pool dump (.json, .jsonl, .csv) or graph snapshot (.snapshot);
+                 defaults to the bundled demo pools
+  --json         print JSON instead of human readable output
+  --min-reserve AMOUNT
+                 skip pools with less than AMOUNT of either token
+  --min-tvl AMOUNT --numeraire TOKEN
+                 skip pools worth less than AMOUNT of TOKEN, at spot prices
+  --pools-per-pair N
+                 keep only the N deepest pools of each token pair
+  --drop-dominated-pools
+                 drop pools that another pool of the pair beats at every size
+  -h, --help     print this message
+
+ALGO is one of auto, best_direct, bfs, dfs, dijkstra, a_star, beam.
+Exit status is 0 on success, 1 when no route is found, 2 on usage errors and
+3 when the pool file cannot be read.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteArgs {
+    pub from: Token,
+    pub to: Token,
+    pub amount: Decimal,
+    pub side: Side,
+    pub slippage_bps: Decimal,
+    pub max_hops: usize,
+    pub algo: RoutingAlgo,
+    pub verify: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbArgs {
+    pub token: Option<Token>,
+    pub amount: Decimal,
+    pub max_hops: usize,
+    pub min_profit: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchArgs {
+    pub iterations: usize,
+    pub amount: Decimal,
+    pub max_hops: usize,
+    pub algo: Option<RoutingAlgo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Quote(QuoteArgs),
+    Arb(ArbArgs),
+    GraphStats,
+    Bench(BenchArgs),
+    Help,
+}
+
+/// Parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub pools: Option<PathBuf>,
+    pub format: Format,
+    /// Thresholds pools must meet to be routed through; quotes check them per request.
+    pub liquidity: LiquidityThresholds,
+    /// Parallel pools of a pair to keep in the loaded graph; all of them when `None`.
+    pub parallel_pools: Option<ParallelPoolPolicy>,
+    pub command: Command,
+}
+
+const DEFAULT_MAX_HOPS: usize = 4;
+
+impl Cli {
+    /// Parses the arguments following the program name.
+    ///
+    /// Global options may appear anywhere on the line.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, CliError> {
+        let mut pools = None;
+        let mut format = Format::Human;
+        let mut liquidity = LiquidityThresholds::default();
+        let mut min_tvl = None;
+        let mut numeraire = None;
+        let mut parallel_pools = None;
+        let mut rest = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--pools" => pools = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--json" => format = Format::Json,
+                "--min-reserve" => liquidity.min_reserve = Some(number(&arg, args.next())?),
+                "--min-tvl" => min_tvl = Some(number(&arg, args.next())?),
+                "--numeraire" => numeraire = Some(Token::intern(&value(&arg, args.next())?)),
+                "--pools-per-pair" => {
+                    let policy = ParallelPoolPolicy::TopN(number(&arg, args.next())?);
+                    set_parallel_pools(&mut parallel_pools, policy)?
+                }
+                "--drop-dominated-pools" => {
+                    let policy = ParallelPoolPolicy::Undominated(TradeSizes::Any);
+                    set_parallel_pools(&mut parallel_pools, policy)?
+                }
+                "-h" | "--help" => {
+                    return Ok(Cli {
+                        pools,
+                        format,
+                        liquidity,
+                        parallel_pools,
+                        command: Command::Help,
+                    });
+                }
+                _ => rest.push(arg),
+            }
+        }
+
+        liquidity.min_tvl = match (min_tvl, numeraire) {
+            (Some(value), Some(numeraire)) => Some(MinTvl { value, numeraire }),
+            (None, None) => None,
+            _ => return Err(usage("--min-tvl and --numeraire go together")),
+        };
+
+        let command = match rest.first().map(String::as_str) {
+            None => return Err(usage("missing command")),
+            Some("quote") => Command::Quote(parse_quote(&rest[1..])?),
+            Some("arb") => Command::Arb(parse_arb(&rest[1..])?),
+            Some("graph") => match rest.get(1).map(String::as_str) {
+                Some("stats") if rest.len() == 2 => Command::GraphStats,
+                _ => return Err(usage("expected `graph stats`")),
+            },
+            Some("bench") => Command::Bench(parse_bench(&rest[1..])?),
+            Some(other) => return Err(usage(&format!("unknown command `{}`", other))),
+        };
+        Ok(Cli {
+            pools,
+            format,
+            liquidity,
+            parallel_pools,
+            command,
+        })
+    }
+}
+
+fn set_parallel_pools(
+    current: &mut Option<ParallelPoolPolicy>,
+    policy: ParallelPoolPolicy,
+) -> Result<(), CliError> {
+    if current.is_some() {
+        return Err(usage(
+            "--pools-per-pair and --drop-dominated-pools are exclusive",
+        ));
+    }
+    *current = Some(policy);
+    Ok(())
+}
+
+fn parse_quote(args: &[String]) -> Result<QuoteArgs, CliError> {
+    let mut positional = Vec::new();
+    let mut quote = QuoteArgs {
+        from: Token(""),
+        to: Token(""),
+        amount: Decimal::ZERO,
+        side: Side::Sell,
+        slippage_bps: Decimal::ZERO,
+        max_hops: DEFAULT_MAX_HOPS,
+        algo: RoutingAlgo::Auto,
+        verify: false,
+    };
+
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--side" => quote.side = parse_side(&value(&arg, args.next())?)?,
+            "--slippage" => quote.slippage_bps = number(&arg, args.next())?,
+            "--max-hops" => quote.max_hops = number(&arg, args.next())?,
+            "--algo" => quote.algo = parse_algo(&value(&arg, args.next())?)?,
+            "--verify" => quote.verify = true,
+            flag if flag.starts_with("--") => return Err(unknown_option(flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let [from, to, amount] = positional.as_slice() else {
+        return Err(usage("quote expects <FROM> <TO> <AMOUNT>"));
+    };
+    quote.from = Token::intern(from);
+    quote.to = Token::intern(to);
+    quote.amount = parse_amount("AMOUNT", amount)?;
+    Ok(quote)
+}
+
+fn parse_arb(args: &[String]) -> Result<ArbArgs, CliError> {
+    let mut arb = ArbArgs {
+        token: None,
+        amount: Decimal::ONE,
+        max_hops: DEFAULT_MAX_HOPS,
+        min_profit: Decimal::ZERO,
+    };
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--token" => arb.token = Some(Token::intern(&value(&arg, args.next())?)),
+            "--amount" => arb.amount = parse_amount(&arg, &value(&arg, args.next())?)?,
+            "--max-hops" => arb.max_hops = number(&arg, args.next())?,
+            "--min-profit" => arb.min_profit = number(&arg, args.next())?,
+            other => return Err(unknown_option(other)),
+        }
+    }
+    Ok(arb)
+}
+
+fn parse_bench(args: &[String]) -> Result<BenchArgs, CliError> {
+    let mut bench = BenchArgs {
+        iterations: 10,
+        amount: Decimal::ONE,
+        max_hops: DEFAULT_MAX_HOPS,
+        algo: None,
+    };
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => bench.iterations = number(&arg, args.next())?,
+            "--amount" => bench.amount = parse_amount(&arg, &value(&arg, args.next())?)?,
+            "--max-hops" => bench.max_hops = number(&arg, args.next())?,
+            "--algo" => bench.algo = Some(parse_algo(&value(&arg, args.next())?)?),
+            other => return Err(unknown_option(other)),
+        }
+    }
+    Ok(bench)
+}
+
+pub fn parse_side(raw: &str) -> Result<Side, CliError> {
+    match raw {
+        // Routes are only searched for a fixed input amount
+        "buy" => Err(usage(
+            "`--side buy` needs exact-out routing, which is not supported",
+        )),
+        "sell" => Ok(Side::Sell),
+        _ => Err(usage(&format!("unknown side `{}`", raw))),
+    }
+}
+
+pub fn parse_algo(raw: &str) -> Result<RoutingAlgo, CliError> {
+    match raw {
+        "auto" => Ok(RoutingAlgo::Auto),
+        "best_direct" => Ok(RoutingAlgo::BestDirect),
+        "bfs" => Ok(RoutingAlgo::Bfs),
+        "dfs" => Ok(RoutingAlgo::Dfs),
+        "dijkstra" => Ok(RoutingAlgo::Dijkstra),
+        "a_star" | "astar" => Ok(RoutingAlgo::AStar),
+        "beam" => Ok(RoutingAlgo::Beam),
+        _ => Err(usage(&format!("unknown algorithm `{}`", raw))),
+    }
+}
+
+fn value(flag: &str, next: Option<String>) -> Result<String, CliError> {
+    next.ok_or_else(|| usage(&format!("{} expects a value", flag)))
+}
+
+fn number<T: FromStr>(flag: &str, next: Option<String>) -> Result<T, CliError> {
+    parse_number(flag, &value(flag, next)?)
+}
+
+fn parse_number<T: FromStr>(name: &str, raw: &str) -> Result<T, CliError> {
+    raw.parse()
+        .map_err(|_| usage(&format!("invalid value `{}` for {}", raw, name)))
+}
+
+/// A trade amount: positive and no larger than the service accepts.
+fn parse_amount(name: &str, raw: &str) -> Result<Decimal, CliError> {
+    let amount = parse_number(name, raw)?;
+    validate_amount(amount).map_err(|error| usage(&format!("invalid {}: {}", name, error)))?;
+    Ok(amount)
+}
+
+fn unknown_option(option: &str) -> CliError {
+    usage(&format!("unexpected argument `{}`", option))
+}
+
+fn usage(message: &str) -> CliError {
+    CliError::Usage(message.to_string())
+}
@@ -0,0 +1,340 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::PruneReason;
+use crate::adapter::pair_math::PairMath;
+use crate::application::quote::{self, QuoteRequest, scan_arbitrage};
+use crate::application::verify::{Verification, verify_route};
+use crate::cli::CliError;
+use crate::cli::args::{ArbArgs, BenchArgs, Format, QuoteArgs};
+use crate::domain::types::{
+    ExecutionParams, LiquidityThresholds, Route, RoutingAlgo, Side, Slippage, Token,
+};
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+
+/// Algorithms timed by `bench` when none is selected.
+const BENCH_ALGOS: [RoutingAlgo; 7] = [
+    RoutingAlgo::BestDirect,
+    RoutingAlgo::Bfs,
+    RoutingAlgo::Dfs,
+    RoutingAlgo::Dijkstra,
+    RoutingAlgo::AStar,
+    RoutingAlgo::Beam,
+    RoutingAlgo::Auto,
+];
+
+/// `bench` quotes every pair among this many best connected tokens.
+const BENCH_MAX_TOKENS: usize = 20;
+
+pub fn quote(
+    graph: &Graph,
+    args: &QuoteArgs,
+    liquidity: &LiquidityThresholds,
+    format: Format,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let request = QuoteRequest {
+        chain_id: graph.chain_id,
+        side: args.side.clone(),
+        from: args.from.clone(),
+        to: args.to.clone(),
+        amount_in: args.amount,
+        params: ExecutionParams {
+            algo: args.algo,
+            slippage: Slippage {
+                tolerance_bps: args.slippage_bps,
+            },
+            max_hops: args.max_hops,
+            liquidity: liquidity.clone(),
+            ..Default::default()
+        },
+    };
+    let Some(quote) = quote::quote(&DefaultRouter, graph, &request) else {
+        return Err(CliError::NoRoute {
+            from: args.from.clone(),
+            to: args.to.clone(),
+        });
+    };
+
+    let verification = if args.verify {
+        let verification = verify_route(&PairMath, graph, &quote.route, quote.amount_in)
+            .map_err(CliError::Verification)?;
+        Some(verification)
+    } else {
+        None
+    };
+
+    match format {
+        Format::Json => {
+            let mut value = json!(quote);
+            if let Some(verification) = &verification {
+                value["verification"] = json!(verification);
+            }
+            writeln!(out, "{}", value)?
+        }
+        Format::Human => {
+            let side = match quote.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            };
+            writeln!(
+                out,
+                "→ SIDE {}: {} {} → {}",
+                side, quote.amount_in, quote.from, quote.to
+            )?;
+            writeln!(out, "→ Best route algo: {:?}", quote.algo)?;
+            writeln!(out, "→ Output: {:.6}", quote.output_amount)?;
+            writeln!(
+                out,
+                "→ Minimum output ({} bps slippage): {:.6}",
+                args.slippage_bps, quote.min_output_amount
+            )?;
+            if quote.route.partial {
+                writeln!(out, "→ Partial: search budget exhausted")?;
+            }
+            if !liquidity.is_empty() {
+                writeln!(out, "→ Liquidity: {}", quote.pruned)?;
+            }
+            write_steps(&quote.route, out)?;
+            if let Some(verification) = &verification {
+                write_verification(verification, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_verification(verification: &Verification, out: &mut dyn Write) -> Result<(), CliError> {
+    writeln!(
+        out,
+        "→ Replayed with pair integer math: {:.6} (discrepancy {}, {:.4}%)",
+        verification.executed,
+        verification.discrepancy(),
+        verification.relative_discrepancy() * Decimal::ONE_HUNDRED
+    )?;
+    for hop in &verification.hops {
+        writeln!(
+            out,
+            "   {} → {}: expected {} executed {}",
+            hop.pool, hop.to, hop.expected, hop.executed
+        )?;
+    }
+    Ok(())
+}
+
+pub fn arb(
+    graph: &Graph,
+    args: &ArbArgs,
+    format: Format,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let params = ExecutionParams {
+        max_hops: args.max_hops,
+        ..Default::default()
+    };
+    let tokens: Vec<Token> = match &args.token {
+        Some(token) => vec![token.clone()],
+        None => sorted_tokens(graph),
+    };
+    let found = scan_arbitrage(graph, &tokens, args.amount, args.min_profit, &params);
+
+    match format {
+        Format::Json => writeln!(out, "{}", json!(found))?,
+        Format::Human => {
+            if found.is_empty() {
+                writeln!(out, "No arbitrage found")?;
+            }
+            for opportunity in &found {
+                writeln!(
+                    out,
+                    "→ Arbitrage Type Circular from {}: {} | Profit: {:.6}",
+                    opportunity.token,
+                    path(&opportunity.arbitrage.route),
+                    opportunity.profit
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn graph_stats(graph: &Graph, format: Format, out: &mut dyn Write) -> Result<(), CliError> {
+    let tokens = graph.adjacency.len();
+    let pools = graph.pools.len();
+    let average_degree = if tokens == 0 {
+        0.0
+    } else {
+        2.0 * pools as f64 / tokens as f64
+    };
+    let busiest = graph
+        .adjacency
+        .iter()
+        .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.0.cmp(a.0.0)))
+        .map(|(token, pools)| (token.clone(), pools.len()));
+
+    match format {
+        Format::Json => {
+            let stats = json!({
+                "tokens": tokens,
+                "pools": pools,
+                "block_number": graph.block_number,
+                "average_degree": average_degree,
+                "max_degree": busiest.as_ref().map(|(token, degree)| json!({
+                    "token": token,
+                    "pools": degree,
+                })),
+                "tokens_with_metadata": graph.token_info.len(),
+                "pruned": {
+                    "pools": graph.pruned.pruned.len(),
+                    "low_reserve": graph.pruned.count(PruneReason::LowReserve),
+                    "low_tvl": graph.pruned.count(PruneReason::LowTvl),
+                    "unpriced": graph.pruned.count(PruneReason::Unpriced),
+                },
+            });
+            writeln!(out, "{}", stats)?;
+        }
+        Format::Human => {
+            writeln!(out, "→ Tokens: {}", tokens)?;
+            writeln!(out, "→ Pools: {}", pools)?;
+            writeln!(out, "→ Block number: {}", graph.block_number)?;
+            writeln!(out, "→ Average pools per token: {:.2}", average_degree)?;
+            if let Some((token, degree)) = busiest {
+                writeln!(out, "→ Most connected token: {} ({} pools)", token, degree)?;
+            }
+            writeln!(out, "→ Tokens with metadata: {}", graph.token_info.len())?;
+            if !graph.liquidity.is_empty() {
+                writeln!(out, "→ Liquidity: {}", graph.pruned)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+struct BenchResult {
+    algo: RoutingAlgo,
+    quotes: usize,
+    found: usize,
+    elapsed: Duration,
+}
+
+pub fn bench(
+    graph: &Graph,
+    args: &BenchArgs,
+    format: Format,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let mut tokens: Vec<Token> = sorted_tokens(graph);
+    tokens.sort_by_key(|token| std::cmp::Reverse(graph.adjacency[token].len()));
+    tokens.truncate(BENCH_MAX_TOKENS);
+    let pairs: Vec<(&Token, &Token)> = tokens
+        .iter()
+        .flat_map(|from| tokens.iter().map(move |to| (from, to)))
+        .filter(|(from, to)| from != to)
+        .collect();
+
+    let algos = match args.algo {
+        Some(algo) => vec![algo],
+        None => BENCH_ALGOS.to_vec(),
+    };
+    let results: Vec<BenchResult> = algos
+        .into_iter()
+        .map(|algo| {
+            let params = ExecutionParams {
+                algo,
+                max_hops: args.max_hops,
+                ..Default::default()
+            };
+            let started = Instant::now();
+            let mut found = 0;
+            for _ in 0..args.iterations {
+                for (from, to) in &pairs {
+                    let route = DefaultRouter.compute_route(
+                        Side::Sell,
+                        graph,
+                        from,
+                        to,
+                        args.amount,
+                        params.clone(),
+                    );
+                    found += usize::from(route.is_some());
+                }
+            }
+            BenchResult {
+                algo,
+                quotes: pairs.len() * args.iterations,
+                found,
+                elapsed: started.elapsed(),
+            }
+        })
+        .collect();
+
+    let mean_micros =
+        |result: &BenchResult| result.elapsed.as_secs_f64() * 1e6 / result.quotes.max(1) as f64;
+    match format {
+        Format::Json => {
+            let report: Vec<_> = results
+                .iter()
+                .map(|result| {
+                    json!({
+                        "algo": result.algo,
+                        "quotes": result.quotes,
+                        "found": result.found,
+                        "total_ms": result.elapsed.as_secs_f64() * 1e3,
+                        "mean_us": mean_micros(result),
+                    })
+                })
+                .collect();
+            writeln!(out, "{}", json!(report))?;
+        }
+        Format::Human => {
+            writeln!(
+                out,
+                "→ {} pairs over {} tokens, {} iterations",
+                pairs.len(),
+                tokens.len(),
+                args.iterations
+            )?;
+            for result in &results {
+                writeln!(
+                    out,
+                    "  {:<12} {:>8} quotes {:>8} found {:>10.1} µs/quote",
+                    format!("{:?}", result.algo),
+                    result.quotes,
+                    result.found,
+                    mean_micros(result)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sorted_tokens(graph: &Graph) -> Vec<Token> {
+    let mut tokens: Vec<Token> = graph.adjacency.keys().cloned().collect();
+    tokens.sort_by_key(|token| token.0);
+    tokens
+}
+
+fn path(route: &Route) -> String {
+    let mut hops: Vec<&str> = route.steps.iter().map(|step| step.from.0).collect();
+    if let Some(last) = route.steps.last() {
+        hops.push(last.to.0);
+    }
+    hops.join(" → ")
+}
+
+fn write_steps(route: &Route, out: &mut dyn Write) -> Result<(), CliError> {
+    writeln!(out, "→ Steps: {} hops", route.steps.len())?;
+    for step in &route.steps {
+        writeln!(
+            out,
+            "  {} → {} via {} on {} [{} / {}]",
+            step.from, step.to, step.pool.id, step.pool.venue, step.pool.token0, step.pool.token1
+        )?;
+    }
+    Ok(())
+}
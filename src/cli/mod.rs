@@ -0,0 +1,108 @@
+//! Command-line front end: `quote`, `arb`, `graph stats` and `bench` over a pool file.
+
+pub mod args;
+mod commands;
+
+use std::fmt::Display;
+use std::io::Write;
+use std::path::Path;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::loader::{LoadError, load_graph, parse_csv};
+use crate::adapter::parallel_pools::prune_parallel_pools;
+use crate::adapter::snapshot::load_snapshot;
+use crate::cli::args::{Cli, Command, USAGE};
+use crate::domain::types::{LiquidityThresholds, Token};
+use crate::port::verification::ExecutionError;
+
+/// Pools used when no `--pools` file is given.
+const DEMO_POOLS: &str = include_str!("../../data/demo_pools.csv");
+
+#[derive(Debug)]
+pub enum CliError {
+    /// Malformed command line.
+    Usage(String),
+    /// The pool file cannot be read or parsed.
+    Input(String),
+    NoRoute {
+        from: Token,
+        to: Token,
+    },
+    /// Writing the output failed.
+    Output(std::io::Error),
+    /// `quote --verify` could not replay the route.
+    Verification(ExecutionError),
+}
+
+impl CliError {
+    /// Process exit status reported for this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::NoRoute { .. } | CliError::Output(_) | CliError::Verification(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Input(_) => 3,
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{} (see --help)", message),
+            CliError::Input(message) => write!(f, "{}", message),
+            CliError::NoRoute { from, to } => write!(f, "no route found for {} → {}", from, to),
+            CliError::Output(err) => write!(f, "cannot write output: {}", err),
+            CliError::Verification(err) => write!(f, "verification failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        CliError::Output(err)
+    }
+}
+
+/// Runs the command line `args` (without the program name), writing results to `out`.
+pub fn run(args: impl IntoIterator<Item = String>, out: &mut dyn Write) -> Result<(), CliError> {
+    let cli = Cli::parse(args)?;
+    if cli.command == Command::Help {
+        writeln!(out, "{}", USAGE)?;
+        return Ok(());
+    }
+
+    // Quotes apply the thresholds to their request instead, so they can report what was pruned
+    let graph_liquidity = match &cli.command {
+        Command::Quote(_) => LiquidityThresholds::default(),
+        _ => cli.liquidity.clone(),
+    };
+    let mut graph = match &cli.pools {
+        Some(path) => load_graph_file(path)?,
+        None => parse_csv(DEMO_POOLS)
+            .and_then(|dump| dump.graph().map_err(LoadError::from))
+            .expect("bundled demo pools are valid"),
+    }
+    .with_liquidity(graph_liquidity);
+    if let Some(policy) = &cli.parallel_pools {
+        graph = prune_parallel_pools(&graph, policy).0;
+    }
+
+    match &cli.command {
+        Command::Quote(quote) => commands::quote(&graph, quote, &cli.liquidity, cli.format, out),
+        Command::Arb(arb) => commands::arb(&graph, arb, cli.format, out),
+        Command::GraphStats => commands::graph_stats(&graph, cli.format, out),
+        Command::Bench(bench) => commands::bench(&graph, bench, cli.format, out),
+        Command::Help => unreachable!("handled above"),
+    }
+}
+
+/// Loads a graph from a pool dump, or from a binary snapshot for `.snapshot` files.
+pub fn load_graph_file(path: &Path) -> Result<Graph, CliError> {
+    let loaded = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("snapshot") => load_snapshot(path).map_err(|err| err.to_string()),
+        _ => load_graph(path).map_err(|err| err.to_string()),
+    };
+    loaded.map_err(|message| CliError::Input(format!("{}: {}", path.display(), message)))
+}
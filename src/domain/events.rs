@@ -0,0 +1,198 @@
+use std::fmt::Display;
+
+use crate::domain::types::Address;
+
+/// `keccak256("Sync(uint112,uint112)")`
+pub const SYNC_TOPIC: [u8; 32] =
+    topic("1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1");
+/// `keccak256("Swap(address,uint256,uint256,uint256,uint256,address)")`
+pub const SWAP_TOPIC: [u8; 32] =
+    topic("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+/// `keccak256("Mint(address,uint256,uint256)")`
+pub const MINT_TOPIC: [u8; 32] =
+    topic("4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f");
+/// `keccak256("Burn(address,uint256,uint256,address)")`
+pub const BURN_TOPIC: [u8; 32] =
+    topic("dccd412f0b1252819cb1fd330b93224ca42612892bb3f4f789976e6d81936496");
+
+const fn topic(hex: &str) -> [u8; 32] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex digit in topic"),
+        }
+    }
+    let bytes = hex.as_bytes();
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        out[i] = (nibble(bytes[2 * i]) << 4) | nibble(bytes[2 * i + 1]);
+        i += 1;
+    }
+    out
+}
+
+pub type BlockHash = [u8; 32];
+
+/// Identifies a block and links it to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub hash: BlockHash,
+    pub parent_hash: BlockHash,
+}
+
+/// A log as returned by `eth_getLogs`, before decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawLog {
+    /// Address of the pair contract that emitted the log.
+    pub address: Address,
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+/// Uniswap V2 pair events that affect reserves.
+///
+/// Amounts are raw integer token units as emitted on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairEvent {
+    Sync {
+        reserve0: u128,
+        reserve1: u128,
+    },
+    Swap {
+        sender: Address,
+        to: Address,
+        amount0_in: u128,
+        amount1_in: u128,
+        amount0_out: u128,
+        amount1_out: u128,
+    },
+    Mint {
+        sender: Address,
+        amount0: u128,
+        amount1: u128,
+    },
+    Burn {
+        sender: Address,
+        to: Address,
+        amount0: u128,
+        amount1: u128,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventError {
+    /// The first topic is not one of the V2 pair events.
+    UnknownTopic([u8; 32]),
+    MissingTopics {
+        expected: usize,
+        actual: usize,
+    },
+    DataLength {
+        expected: usize,
+        actual: usize,
+    },
+    /// A uint256 word does not fit in 128 bits.
+    AmountOverflow,
+}
+
+impl Display for EventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventError::UnknownTopic(topic) => {
+                write!(f, "unknown event topic 0x{}", hex::encode(topic))
+            }
+            EventError::MissingTopics { expected, actual } => {
+                write!(f, "expected {} topics, got {}", expected, actual)
+            }
+            EventError::DataLength { expected, actual } => {
+                write!(f, "expected {} bytes of data, got {}", expected, actual)
+            }
+            EventError::AmountOverflow => write!(f, "amount does not fit in 128 bits"),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+impl PairEvent {
+    /// Decodes a pair log from its topics and ABI-encoded data.
+    pub fn decode(log: &RawLog) -> Result<Self, EventError> {
+        let signature = log.topics.first().ok_or(EventError::MissingTopics {
+            expected: 1,
+            actual: 0,
+        })?;
+
+        match *signature {
+            SYNC_TOPIC => {
+                let [reserve0, reserve1] = words(log, 1)?;
+                Ok(PairEvent::Sync { reserve0, reserve1 })
+            }
+            SWAP_TOPIC => {
+                let [amount0_in, amount1_in, amount0_out, amount1_out] = words(log, 3)?;
+                Ok(PairEvent::Swap {
+                    sender: topic_address(&log.topics[1]),
+                    to: topic_address(&log.topics[2]),
+                    amount0_in,
+                    amount1_in,
+                    amount0_out,
+                    amount1_out,
+                })
+            }
+            MINT_TOPIC => {
+                let [amount0, amount1] = words(log, 2)?;
+                Ok(PairEvent::Mint {
+                    sender: topic_address(&log.topics[1]),
+                    amount0,
+                    amount1,
+                })
+            }
+            BURN_TOPIC => {
+                let [amount0, amount1] = words(log, 3)?;
+                Ok(PairEvent::Burn {
+                    sender: topic_address(&log.topics[1]),
+                    to: topic_address(&log.topics[2]),
+                    amount0,
+                    amount1,
+                })
+            }
+            other => Err(EventError::UnknownTopic(other)),
+        }
+    }
+}
+
+/// Checks the topic count and splits the data into `N` uint256 words.
+fn words<const N: usize>(log: &RawLog, topics: usize) -> Result<[u128; N], EventError> {
+    if log.topics.len() < topics {
+        return Err(EventError::MissingTopics {
+            expected: topics,
+            actual: log.topics.len(),
+        });
+    }
+    if log.data.len() != N * 32 {
+        return Err(EventError::DataLength {
+            expected: N * 32,
+            actual: log.data.len(),
+        });
+    }
+
+    let mut out = [0u128; N];
+    for (slot, word) in out.iter_mut().zip(log.data.chunks_exact(32)) {
+        if word[..16].iter().any(|&b| b != 0) {
+            return Err(EventError::AmountOverflow);
+        }
+        *slot = u128::from_be_bytes(word[16..].try_into().expect("16 byte half word"));
+    }
+    Ok(out)
+}
+
+/// Indexed address parameters are left-padded to 32 bytes.
+fn topic_address(topic: &[u8; 32]) -> Address {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&topic[12..]);
+    Address(address)
+}
@@ -1 +1,5 @@
+pub mod events;
+#[cfg(feature = "serde")]
+pub mod serialization;
 pub mod types;
+pub mod venue;
@@ -0,0 +1,161 @@
+//! Serde support for the domain types (enabled by the `serde` feature).
+//!
+//! Decimal amounts are written as strings so that no precision is lost, tokens
+//! and pool IDs as plain strings, and addresses as `0x` hex. Routes reference
+//! their pools by ID instead of embedding them:
+//!
+//! ```json
+//! {"steps": [{"from": "ETH", "to": "USDC", "pool": "eth-usdc-1", "venue": "uniswap"}],
+//!  "output_amount": "19701.1", "partial": false}
+//! ```
+//!
+//! A route alone does not carry pool state, so it is read back as a
+//! `RouteRecord` and resolved against the pools it was computed on.
+//!
+//! Deserialized input may be untrusted, so tokens, pool IDs and venues are only
+//! read back if a graph or a loader already knows them (see `interned`) and are
+//! rejected otherwise; interning them would leak every name a client makes up.
+
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::domain::types::{Address, Pool, PoolId, Route, SwapStep, Token, Venue, interned};
+
+impl Serialize for Token {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Token {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known(deserializer, "token").map(Token)
+    }
+}
+
+impl Serialize for PoolId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PoolId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known(deserializer, "pool").map(PoolId)
+    }
+}
+
+impl Serialize for Venue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Venue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known(deserializer, "venue").map(Venue)
+    }
+}
+
+/// The `'static` copy of a known name, or an error naming the unknown one.
+fn known<'de, D: Deserializer<'de>>(deserializer: D, kind: &str) -> Result<&'static str, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    interned(&name).ok_or_else(|| D::Error::custom(format!("unknown {} `{}`", kind, name)))
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serialized form of a `SwapStep`, with the pool referenced by ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapStepRecord {
+    pub from: Token,
+    pub to: Token,
+    pub pool: PoolId,
+    /// Venue of the pool, for display; pools are resolved by ID alone.
+    #[serde(default)]
+    pub venue: Venue,
+}
+
+/// Serialized form of a `Route`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRecord {
+    pub steps: Vec<SwapStepRecord>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub output_amount: Decimal,
+    #[serde(default)]
+    pub partial: bool,
+}
+
+impl From<&SwapStep> for SwapStepRecord {
+    fn from(step: &SwapStep) -> Self {
+        SwapStepRecord {
+            from: step.from.clone(),
+            to: step.to.clone(),
+            pool: step.pool.id.clone(),
+            venue: step.pool.venue.clone(),
+        }
+    }
+}
+
+impl From<&Route> for RouteRecord {
+    fn from(route: &Route) -> Self {
+        RouteRecord {
+            steps: route.steps.iter().map(SwapStepRecord::from).collect(),
+            output_amount: route.output_amount,
+            partial: route.partial,
+        }
+    }
+}
+
+impl RouteRecord {
+    /// Rebuilds the route, looking every pool up by ID.
+    ///
+    /// Returns `None` when a pool is unknown to `lookup` or does not trade the
+    /// step's tokens. The output amount is kept as recorded, not re-priced.
+    pub fn resolve(&self, lookup: impl Fn(&PoolId) -> Option<Arc<Pool>>) -> Option<Route> {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let pool = lookup(&step.pool).filter(|pool| pool.supports(&step.from, &step.to))?;
+                Some(SwapStep {
+                    from: step.from.clone(),
+                    to: step.to.clone(),
+                    pool,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Route {
+            steps,
+            output_amount: self.output_amount,
+            partial: self.partial,
+        })
+    }
+}
+
+impl Serialize for SwapStep {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SwapStepRecord::from(self).serialize(serializer)
+    }
+}
+
+impl Serialize for Route {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RouteRecord::from(self).serialize(serializer)
+    }
+}
@@ -1,8 +1,53 @@
-use std::{fmt::Display, sync::Arc};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+    time::Duration,
+};
 
 use rust_decimal::{Decimal, dec};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+fn interned_names() -> MutexGuard<'static, HashSet<&'static str>> {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    INTERNED
+        .get_or_init(|| {
+            let venues = [Venue::UNISWAP, Venue::SUSHISWAP, Venue::PANCAKESWAP];
+            Mutex::new(venues.map(|venue| venue.0).into())
+        })
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns a `'static` copy of `value`, leaking it only the first time it is seen.
+///
+/// Tokens and pool IDs borrow `'static` strings; this lets values read at runtime
+/// (files, chain data) be used for them without leaking one copy per lookup.
+/// Only trusted loaders should intern: every new value leaks for good.
+pub fn intern(value: &str) -> &'static str {
+    let mut interned = interned_names();
+    if let Some(existing) = interned.get(value) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(value.to_owned().into_boxed_str());
+    interned.insert(leaked);
+    leaked
+}
+
+/// Returns the `'static` copy of `value` if it was interned or is a name of a
+/// graph, without leaking anything otherwise.
+pub fn interned(value: &str) -> Option<&'static str> {
+    interned_names().get(value).copied()
+}
+
+/// Makes `'static` names, such as those of a graph built from literals, known to `interned`.
+pub(crate) fn register_names(names: impl IntoIterator<Item = &'static str>) {
+    interned_names().extend(names);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Side {
     Buy,
     Sell,
@@ -11,13 +56,116 @@ pub enum Side {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Token(pub &'static str);
 
-#[derive(Debug, Clone)]
+/// EIP-155 chain ID of the network a token or pool lives on.
+pub type ChainId = u64;
+
+/// Ethereum mainnet, the chain of pools that do not specify one.
+pub const MAINNET: ChainId = 1;
+/// BNB Smart Chain.
+pub const BSC: ChainId = 56;
+/// Arbitrum One.
+pub const ARBITRUM: ChainId = 42161;
+/// Base.
+pub const BASE: ChainId = 8453;
+
+#[cfg(feature = "serde")]
+fn mainnet() -> ChainId {
+    MAINNET
+}
+
+impl Token {
+    pub fn intern(symbol: &str) -> Self {
+        Token(intern(symbol))
+    }
+}
+
+/// 20-byte EVM address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Address(pub [u8; 20]);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAddress(pub String);
+
+impl Display for InvalidAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid address {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAddress {}
+
+impl FromStr for Address {
+    type Err = InvalidAddress;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(digits, &mut bytes).map_err(|_| InvalidAddress(s.to_owned()))?;
+        Ok(Address(bytes))
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+/// The exchange a pool was deployed by, such as Uniswap or one of its V2 forks.
+///
+/// Venues share the constant-product math but differ in fee, factory and
+/// router; see `VenueConfig`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Venue(pub &'static str);
+
+impl Venue {
+    pub const UNISWAP: Venue = Venue("uniswap");
+    pub const SUSHISWAP: Venue = Venue("sushiswap");
+    pub const PANCAKESWAP: Venue = Venue("pancakeswap");
+
+    pub fn intern(name: &str) -> Self {
+        Venue(intern(name))
+    }
+}
+
+/// Pools that do not say otherwise are Uniswap pools.
+impl Default for Venue {
+    fn default() -> Self {
+        Venue::UNISWAP
+    }
+}
+
+/// Stable identifier of a pool, typically the pair contract address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolId(pub &'static str);
+
+impl PoolId {
+    pub fn intern(id: &str) -> Self {
+        PoolId(intern(id))
+    }
+
+    /// Pool ID of the pair deployed at `address`, as a lowercase `0x` hex string.
+    pub fn from_address(address: &Address) -> Self {
+        Self::intern(&address.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pool {
+    pub id: PoolId,
     pub token0: Token,
     pub token1: Token,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
     pub reserve0: Decimal,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
     pub reserve1: Decimal,
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
     pub fee_bps: Decimal,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub venue: Venue,
+    #[cfg_attr(feature = "serde", serde(default = "mainnet"))]
+    pub chain_id: ChainId,
 }
 
 impl Pool {
@@ -25,9 +173,13 @@ impl Pool {
         (a == &self.token0 && b == &self.token1) || (a == &self.token1 && b == &self.token0)
     }
 
+    /// Output of swapping `input_amount` of `input_token` through the pool.
+    ///
+    /// `None` when the pool does not trade `input_token`, or when the amounts
+    /// overflow or the pool is empty.
     pub fn get_output_amount(&self, input_token: &Token, input_amount: Decimal) -> Option<Decimal> {
-        let fee_multiplier = dec!(1) - (self.fee_bps as Decimal / dec!(10000));
-        let input_amount_with_fee = input_amount * fee_multiplier;
+        let fee_multiplier = dec!(1).checked_sub(self.fee_bps.checked_div(dec!(10000))?)?;
+        let input_amount_with_fee = input_amount.checked_mul(fee_multiplier)?;
 
         let (reserve_in, reserve_out) = if input_token == &self.token0 {
             (self.reserve0, self.reserve1)
@@ -37,8 +189,9 @@ impl Pool {
             return None;
         };
 
-        let output = (input_amount_with_fee * reserve_out) / (reserve_in + input_amount_with_fee);
-        Some(output)
+        input_amount_with_fee
+            .checked_mul(reserve_out)?
+            .checked_div(reserve_in.checked_add(input_amount_with_fee)?)
     }
 
     pub fn get_other_token(&self, token: &Token) -> Option<Token> {
@@ -52,6 +205,17 @@ impl Pool {
     }
 }
 
+/// Metadata of a token, as published in a Uniswap token list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenInfo {
+    pub chain_id: ChainId,
+    pub address: Address,
+    pub symbol: Token,
+    pub decimals: u32,
+    pub tags: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct SwapStep {
     pub from: Token,
@@ -63,13 +227,36 @@ pub struct SwapStep {
 pub struct Route {
     pub steps: Vec<SwapStep>,
     pub output_amount: Decimal,
+    /// `true` when the search ran out of budget and this is only the best route found so far.
+    pub partial: bool,
+}
+
+impl Route {
+    /// Marks the route as the best result of a search that was cut short.
+    pub fn into_partial(self) -> Self {
+        Self {
+            partial: true,
+            ..self
+        }
+    }
 }
 
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
-    
+}
+
+impl Display for Venue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for PoolId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -77,37 +264,224 @@ pub struct FeeParams {
     pub fee_bps: Decimal,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Slippage {
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
     pub tolerance_bps: Decimal,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum RoutingAlgo {
     BestDirect,
     Bfs,
     Dfs,
     Dijkstra,
     AStar,
+    Beam,
+    #[default]
     Auto,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Limits how much work a single routing request may do.
+///
+/// `None` means unlimited. `max_expansions` is counted per algorithm, so in
+/// `RoutingAlgo::Auto` mode each competing search gets the same allowance, while
+/// `max_duration` is measured from the start of the request.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SearchBudget {
+    pub max_duration: Option<Duration>,
+    pub max_expansions: Option<usize>,
+}
+
+impl SearchBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_duration.is_none() && self.max_expansions.is_none()
+    }
+}
+
+/// Liquidity a pool needs to be routed through. Unset thresholds are not checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LiquidityThresholds {
+    /// Minimum reserve on each side of a pool, in token units.
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str_option"))]
+    pub min_reserve: Option<Decimal>,
+    /// Minimum total value locked in a pool.
+    pub min_tvl: Option<MinTvl>,
+}
+
+impl LiquidityThresholds {
+    pub fn is_empty(&self) -> bool {
+        self.min_reserve.is_none() && self.min_tvl.is_none()
+    }
+
+    /// Returns `true` if both reserves of `pool` reach `min_reserve`.
+    pub fn has_min_reserve(&self, pool: &Pool) -> bool {
+        self.min_reserve
+            .is_none_or(|min| pool.reserve0 >= min && pool.reserve1 >= min)
+    }
+}
+
+/// A TVL threshold, valued in units of `numeraire`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MinTvl {
+    #[cfg_attr(feature = "serde", serde(with = "rust_decimal::serde::str"))]
+    pub value: Decimal,
+    pub numeraire: Token,
+}
+
+/// Number of partial routes kept per token by `RoutingAlgo::Beam` when not overridden.
+pub const DEFAULT_BEAM_WIDTH: usize = 4;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ExecutionParams {
     pub algo: RoutingAlgo,
     pub slippage: Slippage,
     pub max_hops: usize,
+    /// Partial routes kept per token at each hop by `RoutingAlgo::Beam`.
+    pub beam_width: usize,
+    /// Time and expansion budget; searches stop early and return a partial route when exceeded.
+    pub budget: SearchBudget,
+    /// When set, routes may only go through these tokens, endpoints included.
+    pub allowed_tokens: Option<Arc<HashSet<Token>>>,
+    /// Tokens routes never go through, endpoints included.
+    pub excluded_tokens: Option<Arc<HashSet<Token>>>,
+    /// When set, routes may only go through tokens whose address in the graph's
+    /// token metadata is one of these, endpoints included. Unlike symbols,
+    /// addresses cannot be spoofed by another token.
+    pub allowed_addresses: Option<Arc<HashSet<Address>>>,
+    /// When set, routes may only pass through these connector tokens between their endpoints.
+    pub allowed_intermediates: Option<Arc<HashSet<Token>>>,
+    /// Tokens routes never pass through between their endpoints.
+    pub excluded_intermediates: Option<Arc<HashSet<Token>>>,
+    /// When set, routes may only swap through these pools.
+    pub allowed_pools: Option<Arc<HashSet<PoolId>>>,
+    /// Pools routes never swap through.
+    pub excluded_pools: Option<Arc<HashSet<PoolId>>>,
+    /// When set, routes may only use pools of these venues.
+    pub allowed_venues: Option<Arc<HashSet<Venue>>>,
+    /// Venues whose pools routes never use, even when allowed above.
+    pub excluded_venues: Option<Arc<HashSet<Venue>>>,
+    /// Pools below these thresholds are skipped, on top of the graph's own thresholds.
+    pub liquidity: LiquidityThresholds,
 }
 
+impl ExecutionParams {
+    /// Returns `true` if `token` may appear on a route under these parameters.
+    pub fn allows_token(&self, token: &Token) -> bool {
+        filter_allows(&self.allowed_tokens, &self.excluded_tokens, token)
+    }
+
+    /// Returns `true` if a route under these parameters may pass through `token`
+    /// between its endpoints.
+    pub fn allows_intermediate(&self, token: &Token) -> bool {
+        self.allows_token(token)
+            && filter_allows(
+                &self.allowed_intermediates,
+                &self.excluded_intermediates,
+                token,
+            )
+    }
+
+    /// Returns `true` if a route under these parameters may swap through `pool`.
+    ///
+    /// The TVL threshold depends on prices across the whole graph and is not
+    /// checked here; see `adapter::liquidity::apply_thresholds`.
+    pub fn allows_pool(&self, pool: &Pool) -> bool {
+        filter_allows(&self.allowed_venues, &self.excluded_venues, &pool.venue)
+            && filter_allows(&self.allowed_pools, &self.excluded_pools, &pool.id)
+            && self.liquidity.has_min_reserve(pool)
+    }
 
+    /// Returns `true` if every step of a route made of `steps` is allowed.
+    pub fn allows_steps(&self, steps: &[SwapStep]) -> bool {
+        let (Some(first), Some(last)) = (steps.first(), steps.last()) else {
+            return true;
+        };
+        self.allows_token(&first.from)
+            && self.allows_token(&last.to)
+            && steps.iter().all(|step| self.allows_pool(&step.pool))
+            && steps[..steps.len() - 1]
+                .iter()
+                .all(|step| self.allows_intermediate(&step.to))
+    }
+}
+
+/// An item passes when it is in `allowed` (if set) and not in `excluded`.
+fn filter_allows<T: Eq + std::hash::Hash>(
+    allowed: &Option<Arc<HashSet<T>>>,
+    excluded: &Option<Arc<HashSet<T>>>,
+    item: &T,
+) -> bool {
+    allowed
+        .as_ref()
+        .is_none_or(|allowed| allowed.contains(item))
+        && excluded
+            .as_ref()
+            .is_none_or(|excluded| !excluded.contains(item))
+}
+
+impl Default for ExecutionParams {
+    fn default() -> Self {
+        Self {
+            algo: RoutingAlgo::Auto,
+            slippage: Slippage::default(),
+            max_hops: 4,
+            beam_width: DEFAULT_BEAM_WIDTH,
+            budget: SearchBudget::default(),
+            allowed_tokens: None,
+            excluded_tokens: None,
+            allowed_addresses: None,
+            allowed_intermediates: None,
+            excluded_intermediates: None,
+            allowed_pools: None,
+            excluded_pools: None,
+            allowed_venues: None,
+            excluded_venues: None,
+            liquidity: LiquidityThresholds::default(),
+        }
+    }
+}
+
+/// Result of a cancellable routing request.
 #[derive(Debug, Clone)]
+pub enum RouteOutcome {
+    Found(RoutingAlgo, Route),
+    NoRoute,
+    /// The request was cancelled before the search completed.
+    Cancelled,
+}
+
+impl RouteOutcome {
+    /// Returns the route if one was found, dropping the distinction between
+    /// `NoRoute` and `Cancelled`.
+    pub fn found(self) -> Option<(RoutingAlgo, Route)> {
+        match self {
+            RouteOutcome::Found(algo, route) => Some((algo, route)),
+            RouteOutcome::NoRoute | RouteOutcome::Cancelled => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ArbitrageType {
     Circular,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Arbitrage {
     pub arbitrage_type: ArbitrageType,
-    pub route: Route
+    pub route: Route,
 }
-
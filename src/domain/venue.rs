@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::domain::types::{ARBITRUM, Address, BASE, BSC, ChainId, MAINNET, Venue};
+
+/// Deployment of a Uniswap V2 compatible exchange on one chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VenueConfig {
+    pub venue: Venue,
+    pub chain_id: ChainId,
+    /// Swap fee charged by every pair of the venue.
+    pub fee_bps: Decimal,
+    /// Factory deploying the pairs.
+    pub factory: Address,
+    /// `keccak256` of the pair creation code, from which pair addresses are derived.
+    pub init_code_hash: [u8; 32],
+    /// Router to send swaps through.
+    pub router: Address,
+}
+
+/// `keccak256` of the Uniswap V2 pair creation code, the same on every chain it is deployed to.
+const UNISWAP_V2_INIT_CODE_HASH: &str =
+    "96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f";
+
+impl VenueConfig {
+    /// Uniswap V2 on Ethereum mainnet.
+    pub fn uniswap_mainnet() -> Self {
+        Self::uniswap(
+            MAINNET,
+            "5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f",
+            "7a250d5630b4cf539739df2c5dacb4c659f2488d",
+        )
+    }
+
+    /// Uniswap V2 on Arbitrum One.
+    pub fn uniswap_arbitrum() -> Self {
+        Self::uniswap(
+            ARBITRUM,
+            "f1d7cc64fb4452f05c498126312ebe29f30fbcf9",
+            "4752ba5dbc23f44d87826276bf6fd6b1c372ad24",
+        )
+    }
+
+    /// Uniswap V2 on Base.
+    pub fn uniswap_base() -> Self {
+        Self::uniswap(
+            BASE,
+            "8909dc15e40173ff4699343b6eb8132c65e18ec6",
+            "4752ba5dbc23f44d87826276bf6fd6b1c372ad24",
+        )
+    }
+
+    /// PancakeSwap V2 on BNB Smart Chain.
+    pub fn pancakeswap_bsc() -> Self {
+        Self {
+            venue: Venue::PANCAKESWAP,
+            chain_id: BSC,
+            fee_bps: dec!(25),
+            factory: Address(hex_bytes("ca143ce32fe78f1f7019d7d551a6402fc5350c73")),
+            init_code_hash: hex_bytes(
+                "00fb7f630766e6a796048ea87d01acd3068e8ff67d078148a3fa3f4a84f69bd5",
+            ),
+            router: Address(hex_bytes("10ed43c718714eb63d5aa57b78b54704e256024e")),
+        }
+    }
+
+    fn uniswap(chain_id: ChainId, factory: &str, router: &str) -> Self {
+        Self {
+            venue: Venue::UNISWAP,
+            chain_id,
+            fee_bps: dec!(30),
+            factory: Address(hex_bytes(factory)),
+            init_code_hash: hex_bytes(UNISWAP_V2_INIT_CODE_HASH),
+            router: Address(hex_bytes(router)),
+        }
+    }
+}
+
+fn hex_bytes<const N: usize>(digits: &str) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    hex::decode_to_slice(digits, &mut bytes).expect("valid constant");
+    bytes
+}
+
+/// Per-chain configuration of the venues pools may come from.
+#[derive(Debug, Clone, Default)]
+pub struct Venues {
+    configs: HashMap<(ChainId, Venue), VenueConfig>,
+}
+
+impl Venues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Venues whose deployments ship with the router: Uniswap V2 on mainnet,
+    /// Arbitrum and Base, and PancakeSwap V2 on BNB Smart Chain.
+    ///
+    /// Other deployments are added with `with`.
+    pub fn builtin() -> Self {
+        Self::new()
+            .with(VenueConfig::uniswap_mainnet())
+            .with(VenueConfig::uniswap_arbitrum())
+            .with(VenueConfig::uniswap_base())
+            .with(VenueConfig::pancakeswap_bsc())
+    }
+
+    /// Adds `config`, replacing the configuration of the same venue on the same chain.
+    pub fn with(mut self, config: VenueConfig) -> Self {
+        self.configs
+            .insert((config.chain_id, config.venue.clone()), config);
+        self
+    }
+
+    pub fn get(&self, chain_id: ChainId, venue: &Venue) -> Option<&VenueConfig> {
+        self.configs.get(&(chain_id, venue.clone()))
+    }
+
+    /// The venue on `chain_id` whose factory is `factory`.
+    pub fn by_factory(&self, chain_id: ChainId, factory: &Address) -> Option<&VenueConfig> {
+        self.configs
+            .values()
+            .find(|config| config.chain_id == chain_id && config.factory == *factory)
+    }
+
+    /// Default fee of `venue` on `chain_id`.
+    pub fn fee_bps(&self, chain_id: ChainId, venue: &Venue) -> Option<Decimal> {
+        self.get(chain_id, venue).map(|config| config.fee_bps)
+    }
+}
@@ -1 +1,2 @@
 pub mod algo_selector;
+pub mod route_cache;
@@ -0,0 +1,310 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::liquidity::apply_thresholds;
+use crate::application::search_control::CancellationToken;
+use crate::domain::types::{
+    Address, ChainId, ExecutionParams, PoolId, Route, RouteOutcome, RoutingAlgo, Side, SwapStep,
+    Token, Venue,
+};
+use crate::port::routing::RoutingStrategy;
+
+/// Number of candidate paths remembered per cache key when not overridden.
+pub const DEFAULT_MAX_PATHS_PER_KEY: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    side: Side,
+    from: Token,
+    to: Token,
+    algo: RoutingAlgo,
+    bucket: i32,
+    scope: SearchScope,
+}
+
+/// The request parameters that decide which paths a search may return.
+///
+/// A path found under one scope is not the best one under another, even when
+/// it is allowed there, so requests only share paths within the same scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SearchScope {
+    max_hops: usize,
+    beam_width: usize,
+    allowed_tokens: Option<Vec<Token>>,
+    excluded_tokens: Option<Vec<Token>>,
+    allowed_addresses: Option<Vec<Address>>,
+    allowed_intermediates: Option<Vec<Token>>,
+    excluded_intermediates: Option<Vec<Token>>,
+    allowed_pools: Option<Vec<PoolId>>,
+    excluded_pools: Option<Vec<PoolId>>,
+    allowed_venues: Option<Vec<Venue>>,
+    excluded_venues: Option<Vec<Venue>>,
+}
+
+impl SearchScope {
+    /// Scope of `params`, once `apply_thresholds` turned its thresholds into
+    /// excluded pools.
+    fn of(params: &ExecutionParams) -> Self {
+        Self {
+            max_hops: params.max_hops,
+            beam_width: params.beam_width,
+            allowed_tokens: sorted(&params.allowed_tokens, |token| token.0),
+            excluded_tokens: sorted(&params.excluded_tokens, |token| token.0),
+            allowed_addresses: params.allowed_addresses.as_deref().map(|set| {
+                let mut addresses: Vec<Address> = set.iter().copied().collect();
+                addresses.sort_unstable();
+                addresses
+            }),
+            allowed_intermediates: sorted(&params.allowed_intermediates, |token| token.0),
+            excluded_intermediates: sorted(&params.excluded_intermediates, |token| token.0),
+            allowed_pools: sorted(&params.allowed_pools, |id| id.0),
+            excluded_pools: sorted(&params.excluded_pools, |id| id.0),
+            allowed_venues: sorted(&params.allowed_venues, |venue| venue.0),
+            excluded_venues: sorted(&params.excluded_venues, |venue| venue.0),
+        }
+    }
+}
+
+fn sorted<T: Clone>(set: &Option<Arc<HashSet<T>>>, name: fn(&T) -> &'static str) -> Option<Vec<T>> {
+    set.as_deref().map(|set| {
+        let mut items: Vec<T> = set.iter().cloned().collect();
+        items.sort_unstable_by_key(name);
+        items
+    })
+}
+
+/// Paths found on one version of a chain's graph.
+#[derive(Debug, Default)]
+struct Generation {
+    version: u64,
+    paths: HashMap<CacheKey, Vec<CachedPath>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedPath {
+    algo: RoutingAlgo,
+    steps: Vec<SwapStep>,
+}
+
+impl CachedPath {
+    fn reprice(&self, amount_in: Decimal) -> Option<Route> {
+        let output_amount = self.steps.iter().try_fold(amount_in, |amount, step| {
+            step.pool.get_output_amount(&step.from, amount)
+        })?;
+        Some(Route {
+            steps: self.steps.clone(),
+            output_amount,
+            partial: false,
+        })
+    }
+
+    fn same_path(&self, steps: &[SwapStep]) -> bool {
+        self.steps.len() == steps.len()
+            && self
+                .steps
+                .iter()
+                .zip(steps)
+                .all(|(a, b)| a.from == b.from && a.pool.id == b.pool.id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Caching layer around a `RoutingStrategy`.
+///
+/// Paths are stored per (chain, side, from, to, algorithm, amount bucket, search scope),
+/// where buckets are powers of two of the input amount and the scope holds the hop
+/// limit, beam width, filters and reserve threshold of the request. On a hit the cached paths are re-priced for
+/// the requested amount and the best one is returned, without running a search.
+///
+/// Only paths found on the graph version being routed are used: any change to the
+/// graph, even to pools off the cached paths, can make another path the best one.
+/// Invalidation is therefore per chain rather than per pool: the first request
+/// routed on a newer version of a chain's graph, e.g. after a `GraphStore` update
+/// touching a single pool, drops every path cached for that chain. Routing an
+/// older version bypasses the cache.
+pub struct CachedRouter<R> {
+    inner: R,
+    entries: Mutex<HashMap<ChainId, Generation>>,
+    max_paths_per_key: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<R: RoutingStrategy> CachedRouter<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_max_paths_per_key(inner, DEFAULT_MAX_PATHS_PER_KEY)
+    }
+
+    pub fn with_max_paths_per_key(inner: R, max_paths_per_key: usize) -> Self {
+        Self {
+            inner,
+            entries: Mutex::new(HashMap::new()),
+            max_paths_per_key: max_paths_per_key.max(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lookup(
+        &self,
+        key: &CacheKey,
+        graph: &Graph,
+        amount_in: Decimal,
+    ) -> Option<(RoutingAlgo, Route)> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = entries
+            .get(&graph.chain_id)
+            .filter(|generation| generation.version == graph.version())?;
+        generation
+            .paths
+            .get(key)?
+            .iter()
+            .filter_map(|path| path.reprice(amount_in).map(|route| (path.algo, route)))
+            .max_by(|a, b| a.1.output_amount.cmp(&b.1.output_amount))
+    }
+
+    fn store(&self, key: CacheKey, graph: &Graph, algo: RoutingAlgo, route: &Route) {
+        if route.partial || route.steps.is_empty() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = entries.entry(graph.chain_id).or_default();
+        if graph.version() < generation.version {
+            return;
+        }
+        if graph.version() > generation.version {
+            *generation = Generation {
+                version: graph.version(),
+                paths: HashMap::new(),
+            };
+        }
+        let paths = generation.paths.entry(key).or_default();
+        if paths.iter().any(|path| path.same_path(&route.steps)) {
+            return;
+        }
+        if paths.len() >= self.max_paths_per_key {
+            paths.remove(0);
+        }
+        paths.push(CachedPath {
+            algo,
+            steps: route.steps.clone(),
+        });
+    }
+
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<R: RoutingStrategy> RoutingStrategy for CachedRouter<R> {
+    fn compute_route(
+        &self,
+        side: Side,
+        graph: &Graph,
+        from: &Token,
+        to: &Token,
+        amount_in: Decimal,
+        params: ExecutionParams,
+    ) -> Option<(RoutingAlgo, Route)> {
+        let (params, _) = apply_thresholds(graph, params);
+        let key = cache_key(&side, from, to, &params, amount_in);
+        if let Some(found) = self.lookup(&key, graph, amount_in) {
+            self.record(true);
+            return Some(found);
+        }
+        self.record(false);
+
+        let found = self
+            .inner
+            .compute_route(side, graph, from, to, amount_in, params)?;
+        self.store(key, graph, found.0, &found.1);
+        Some(found)
+    }
+
+    fn compute_route_cancellable(
+        &self,
+        side: Side,
+        graph: &Graph,
+        from: &Token,
+        to: &Token,
+        amount_in: Decimal,
+        params: ExecutionParams,
+        cancellation: &CancellationToken,
+    ) -> RouteOutcome {
+        if cancellation.is_cancelled() {
+            return RouteOutcome::Cancelled;
+        }
+        let (params, _) = apply_thresholds(graph, params);
+        let key = cache_key(&side, from, to, &params, amount_in);
+        if let Some((algo, route)) = self.lookup(&key, graph, amount_in) {
+            self.record(true);
+            return RouteOutcome::Found(algo, route);
+        }
+        self.record(false);
+
+        let outcome = self.inner.compute_route_cancellable(
+            side,
+            graph,
+            from,
+            to,
+            amount_in,
+            params,
+            cancellation,
+        );
+        if let RouteOutcome::Found(algo, route) = &outcome {
+            self.store(key, graph, *algo, route);
+        }
+        outcome
+    }
+}
+
+fn cache_key(
+    side: &Side,
+    from: &Token,
+    to: &Token,
+    params: &ExecutionParams,
+    amount_in: Decimal,
+) -> CacheKey {
+    CacheKey {
+        side: side.clone(),
+        from: from.clone(),
+        to: to.clone(),
+        algo: params.algo,
+        bucket: amount_bucket(amount_in),
+        scope: SearchScope::of(params),
+    }
+}
+
+/// Buckets an amount by its power of two, so that 8 and 15 share a bucket but 16 does not.
+fn amount_bucket(amount: Decimal) -> i32 {
+    amount
+        .to_f64()
+        .filter(|value| *value > 0.0)
+        .map_or(i32::MIN, |value| value.log2().floor() as i32)
+}
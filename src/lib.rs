@@ -1,8 +1,12 @@
 pub mod adapter;
 pub mod application;
+#[cfg(feature = "serde")]
+pub mod cli;
 pub mod domain;
 pub mod infra;
 pub mod port;
+#[cfg(feature = "server")]
+pub mod server;
 
 #[cfg(test)]
 mod tests;
@@ -2,7 +2,8 @@ use rust_decimal::Decimal;
 
 use crate::adapter::graph::Graph;
 use crate::application::route_engine;
-use crate::domain::types::{ExecutionParams, Route, RoutingAlgo, Side, Token};
+use crate::application::search_control::CancellationToken;
+use crate::domain::types::{ExecutionParams, Route, RouteOutcome, RoutingAlgo, Side, Token};
 
 pub trait RoutingStrategy {
     fn compute_route(
@@ -14,6 +15,20 @@ pub trait RoutingStrategy {
         amount_in: Decimal,
         params: ExecutionParams,
     ) -> Option<(RoutingAlgo, Route)>;
+
+    /// Like `compute_route`, but stops as soon as `cancellation` is cancelled and
+    /// reports it as `RouteOutcome::Cancelled` rather than as a missing route.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_route_cancellable(
+        &self,
+        side: Side,
+        graph: &Graph,
+        from: &Token,
+        to: &Token,
+        amount_in: Decimal,
+        params: ExecutionParams,
+        cancellation: &CancellationToken,
+    ) -> RouteOutcome;
 }
 
 pub struct DefaultRouter;
@@ -30,4 +45,17 @@ impl RoutingStrategy for DefaultRouter {
     ) -> Option<(RoutingAlgo, Route)> {
         route_engine::execute(side, graph, from, to, amount_in, params)
     }
+
+    fn compute_route_cancellable(
+        &self,
+        side: Side,
+        graph: &Graph,
+        from: &Token,
+        to: &Token,
+        amount_in: Decimal,
+        params: ExecutionParams,
+        cancellation: &CancellationToken,
+    ) -> RouteOutcome {
+        route_engine::execute_cancellable(side, graph, from, to, amount_in, params, cancellation)
+    }
 }
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use rust_decimal::Decimal;
+
+use crate::adapter::graph::Graph;
+use crate::domain::types::{PoolId, Route};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionError {
+    UnknownPool(PoolId),
+    /// The executor cannot represent the pool or an amount, e.g. a fractional fee.
+    Unsupported(String),
+    /// The swap failed the way the pair contract would revert.
+    Reverted {
+        pool: PoolId,
+        reason: String,
+    },
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::UnknownPool(id) => write!(f, "unknown pool {}", id),
+            ExecutionError::Unsupported(message) => write!(f, "unsupported: {}", message),
+            ExecutionError::Reverted { pool, reason } => {
+                write!(f, "swap through {} reverted: {}", pool, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+/// Executes a route independently of the router's own math, to check its quotes.
+///
+/// `adapter::pair_math::PairMath` re-derives the contracts' integer formulas by
+/// hand, so it catches rounding drift in the router's decimal math but not a
+/// mismatch with the deployed contracts. `adapter::evm::EvmExecutor`, behind the
+/// `evm` feature, runs the venues' router and pair bytecode in revm instead.
+pub trait RouteExecutor {
+    /// Swaps `amount_in` along `route` with the reserves of `graph` and returns
+    /// the amount received after each hop.
+    fn execute(
+        &self,
+        graph: &Graph,
+        route: &Route,
+        amount_in: Decimal,
+    ) -> Result<Vec<Decimal>, ExecutionError>;
+}
@@ -0,0 +1,207 @@
+//! Local HTTP/JSON quote server (enabled by the `server` feature).
+//!
+//! | Method | Path           | Body                  | Response                     |
+//! |--------|----------------|-----------------------|------------------------------|
+//! | GET    | `/health`      |                       | `Health`                     |
+//! | POST   | `/quote`       | `QuoteRequest`        | `Quote`, 422 without a route |
+//! | POST   | `/quote/batch` | `[QuoteRequest]`      | `[Quote or {"error"}]`       |
+//! | POST   | `/arbitrage`   | `ArbitrageRequest`    | `[Opportunity]`              |
+//! | POST   | `/pools`       | `PoolUpdateRequest`   | `PoolUpdateResponse`         |
+//! | GET    | `/subscribe`   | WebSocket upgrade     | quote subscriptions          |
+//!
+//! Errors are returned as `{"error": "..."}`; malformed bodies, invalid amounts
+//! and tokens, pools or venues unknown to every loaded graph are a 400. On `/subscribe` clients send
+//! `{"type": "subscribe", "id", ...QuoteRequest}` and `{"type": "unsubscribe", "id"}`
+//! text messages and receive the quote of each subscription whenever a pool
+//! update changes it, see [`subscription`]. The same service can be driven
+//! without a listener through line-delimited JSON-RPC on stdio, see [`rpc`].
+
+pub mod http;
+pub mod rpc;
+pub mod service;
+pub mod subscription;
+pub mod ws;
+
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::application::quote::QuoteRequest;
+use crate::port::routing::RoutingStrategy;
+use crate::server::http::{Request, Response, read_request};
+use crate::server::service::{ArbitrageRequest, PoolUpdateRequest, QuoteService, ServiceError};
+
+/// Maps HTTP requests onto a shared `QuoteService`.
+pub struct QuoteServer<R> {
+    service: Arc<QuoteService<R>>,
+}
+
+impl<R> Clone for QuoteServer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            service: Arc::clone(&self.service),
+        }
+    }
+}
+
+impl<R: RoutingStrategy + Send + Sync + 'static> QuoteServer<R> {
+    pub fn new(service: QuoteService<R>) -> Self {
+        Self {
+            service: Arc::new(service),
+        }
+    }
+
+    pub fn service(&self) -> &QuoteService<R> {
+        &self.service
+    }
+
+    /// Answers one request.
+    pub fn handle(&self, request: &Request) -> Response {
+        let service = &self.service;
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/health") => ok(&service.health()),
+            ("POST", "/quote") => match parse::<QuoteRequest>(&request.body) {
+                Ok(quote) => service.quote(&quote).map_or_else(error, |found| ok(&found)),
+                Err(response) => response,
+            },
+            ("POST", "/quote/batch") => match parse::<Vec<QuoteRequest>>(&request.body) {
+                Ok(requests) => ok(&service.quote_batch(&requests)),
+                Err(response) => response,
+            },
+            ("GET" | "POST", "/arbitrage") => {
+                let parsed = if request.body.is_empty() {
+                    parse::<ArbitrageRequest>(b"{}")
+                } else {
+                    parse::<ArbitrageRequest>(&request.body)
+                };
+                match parsed {
+                    Ok(arbitrage) => service
+                        .arbitrage(&arbitrage)
+                        .map_or_else(error, |found| ok(&found)),
+                    Err(response) => response,
+                }
+            }
+            ("POST", "/pools") => match parse::<PoolUpdateRequest>(&request.body) {
+                Ok(update) => service
+                    .update_pools(&update)
+                    .map_or_else(error, |updated| ok(&updated)),
+                Err(response) => response,
+            },
+            ("GET", "/subscribe") => Response::error(426, "expected a WebSocket upgrade"),
+            (_, "/health" | "/quote" | "/quote/batch" | "/arbitrage" | "/pools" | "/subscribe") => {
+                Response::error(405, format!("{} not allowed here", request.method))
+            }
+            (_, path) => Response::error(404, format!("no endpoint {}", path)),
+        }
+    }
+
+    /// Answers one request on `stream`, or hands it over to a subscription
+    /// session when the client upgrades `/subscribe` to a WebSocket.
+    fn serve_connection(&self, stream: TcpStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let response = match read_request(&mut reader)? {
+            Ok(Some(request)) if request.path == "/subscribe" && ws::is_upgrade(&request) => {
+                return ws::serve(Arc::clone(&self.service), &request, reader);
+            }
+            Ok(Some(request)) => self.handle(&request),
+            Ok(None) => return Ok(()),
+            Err(response) => response,
+        };
+        response.write_to(reader.get_mut())
+    }
+
+    /// Binds `addr` and serves requests on a background thread, one thread per connection.
+    pub fn spawn(&self, addr: impl ToSocketAddrs) -> std::io::Result<ServerHandle> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = self.clone();
+        let stopped = Arc::clone(&stop);
+        let thread = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let server = server.clone();
+                std::thread::spawn(move || {
+                    // A client hanging up mid-request is not worth reporting
+                    let _ = server.serve_connection(stream);
+                });
+            }
+        });
+        Ok(ServerHandle {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// A running server; stops accepting connections when shut down or dropped.
+pub struct ServerHandle {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops the accept loop and waits for it; requests in flight and open
+    /// subscription sessions still complete.
+    pub fn shutdown(mut self) {
+        self.stop_accepting();
+    }
+
+    /// Blocks until the server stops.
+    pub fn join(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    fn stop_accepting(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        self.stop.store(true, Ordering::Release);
+        // Wake the blocking accept so that it notices the flag
+        let _ = TcpStream::connect(self.addr);
+        let _ = thread.join();
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        self.stop_accepting();
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, Response> {
+    serde_json::from_slice(body)
+        .map_err(|err| Response::error(400, format!("invalid body: {}", err)))
+}
+
+fn ok(value: &impl Serialize) -> Response {
+    match serde_json::to_string(value) {
+        Ok(body) => Response::json(200, body),
+        Err(err) => Response::error(500, err),
+    }
+}
+
+fn error(err: ServiceError) -> Response {
+    let status = match err {
+        ServiceError::BadRequest(_) => 400,
+        ServiceError::UnknownPool(_) | ServiceError::UnknownChain(_) => 404,
+        ServiceError::NoRoute { .. } => 422,
+    };
+    Response::error(status, err)
+}
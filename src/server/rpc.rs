@@ -0,0 +1,199 @@
+//! Line-delimited JSON-RPC 2.0, for driving the router from a parent process over
+//! stdin/stdout.
+//!
+//! Each line holds one request; each request with an `id` gets exactly one
+//! response line, in order. Requests without an `id` are notifications and are
+//! not answered.
+//!
+//! | Method            | Params                                   | Result               |
+//! |-------------------|------------------------------------------|----------------------|
+//! | `health`          |                                          | `Health`             |
+//! | `load_pools`      | `{"path"}` or `{"pools": [Pool]}`, plus an optional `block_number` | `Health` |
+//! | `update_reserves` | `PoolUpdateRequest`                      | `PoolUpdateResponse` |
+//! | `quote`           | `QuoteRequest`                           | `Quote`              |
+//! | `quote_batch`     | `[QuoteRequest]`                         | `[Quote or {"error"}]` |
+//! | `arbitrage`       | `ArbitrageRequest`                       | `[Opportunity]`      |
+//!
+//! `load_pools` replaces the graph of the pools' chain, which must be served.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::adapter::graph::Graph;
+use crate::application::quote::QuoteRequest;
+use crate::cli::load_graph_file;
+use crate::domain::types::Pool;
+use crate::port::routing::RoutingStrategy;
+use crate::server::service::{ArbitrageRequest, PoolUpdateRequest, QuoteService, ServiceError};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Application errors, in the range reserved for implementations.
+pub const NO_ROUTE: i64 = -32000;
+pub const UNKNOWN_POOL: i64 = -32001;
+pub const LOAD_FAILED: i64 = -32002;
+pub const UNKNOWN_CHAIN: i64 = -32003;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl std::fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<ServiceError> for RpcError {
+    fn from(err: ServiceError) -> Self {
+        let code = match err {
+            ServiceError::BadRequest(_) => INVALID_PARAMS,
+            ServiceError::NoRoute { .. } => NO_ROUTE,
+            ServiceError::UnknownPool(_) => UNKNOWN_POOL,
+            ServiceError::UnknownChain(_) => UNKNOWN_CHAIN,
+        };
+        RpcError::new(code, err)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadPoolsParams {
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    pools: Option<Vec<Pool>>,
+    #[serde(default)]
+    block_number: Option<u64>,
+}
+
+/// Answers one request line; returns `None` for notifications.
+pub fn handle_line<R: RoutingStrategy + Sync>(
+    service: &QuoteService<R>,
+    line: &str,
+) -> Option<String> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Err(err) => return Some(respond(Value::Null, Err(RpcError::new(PARSE_ERROR, err)))),
+        Ok(value) => match RpcRequest::deserialize(&value) {
+            Ok(request) => request,
+            Err(err) => {
+                let id = value.get("id").cloned().unwrap_or(Value::Null);
+                return Some(respond(id, Err(RpcError::new(INVALID_REQUEST, err))));
+            }
+        },
+    };
+
+    let result = dispatch(service, &request.method, request.params);
+    request.id.map(|id| respond(id, result))
+}
+
+/// Serves requests read from `input` until it is closed, writing responses to `output`.
+pub fn serve<R: RoutingStrategy + Sync>(
+    service: &QuoteService<R>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(service, &line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn dispatch<R: RoutingStrategy + Sync>(
+    service: &QuoteService<R>,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "health" => to_value(service.health()),
+        "load_pools" => {
+            let params: LoadPoolsParams = parse_params(params)?;
+            let graph = match (params.path, params.pools) {
+                (Some(path), None) => {
+                    load_graph_file(&path).map_err(|err| RpcError::new(LOAD_FAILED, err))?
+                }
+                (None, Some(pools)) => {
+                    Graph::try_new(&pools.into_iter().map(Arc::new).collect::<Vec<_>>())
+                        .map_err(|err| RpcError::new(INVALID_PARAMS, err))?
+                }
+                _ => {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        "expected exactly one of `path` and `pools`",
+                    ));
+                }
+            };
+            let block_number = params.block_number.unwrap_or(graph.block_number);
+            service
+                .registry()
+                .publish(graph.with_block_number(block_number))
+                .map_err(ServiceError::from)?;
+            to_value(service.health())
+        }
+        "update_reserves" => {
+            let params: PoolUpdateRequest = parse_params(params)?;
+            to_value(service.update_pools(&params)?)
+        }
+        "quote" => {
+            let params: QuoteRequest = parse_params(params)?;
+            to_value(service.quote(&params)?)
+        }
+        "quote_batch" => {
+            let params: Vec<QuoteRequest> = parse_params(params)?;
+            to_value(service.quote_batch(&params))
+        }
+        "arbitrage" => {
+            let params = if params.is_null() { json!({}) } else { params };
+            let params: ArbitrageRequest = parse_params(params)?;
+            to_value(service.arbitrage(&params)?)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method {}", method),
+        )),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::new(INTERNAL_ERROR, err))
+}
+
+fn respond(id: Value, result: Result<Value, RpcError>) -> String {
+    let response = match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+    };
+    response.to_string()
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::adapter::graph_registry::{GraphRegistry, RegistryError};
+use crate::adapter::graph_store::GraphStore;
+use crate::application::quote::{
+    Opportunity, Quote, QuoteRequest, quote, scan_arbitrage, validate_amount,
+};
+use crate::domain::types::{ChainId, ExecutionParams, MAINNET, PoolId, Token};
+use crate::port::routing::RoutingStrategy;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceError {
+    /// The request body or parameters are invalid.
+    BadRequest(String),
+    NoRoute {
+        from: Token,
+        to: Token,
+    },
+    UnknownPool(PoolId),
+    UnknownChain(ChainId),
+}
+
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceError::BadRequest(message) => write!(f, "bad request: {}", message),
+            ServiceError::NoRoute { from, to } => write!(f, "no route found for {} → {}", from, to),
+            ServiceError::UnknownPool(id) => write!(f, "unknown pool {}", id),
+            ServiceError::UnknownChain(chain_id) => write!(f, "chain {} is not served", chain_id),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<GraphError> for ServiceError {
+    fn from(err: GraphError) -> Self {
+        match err {
+            GraphError::UnknownPool(id) => ServiceError::UnknownPool(id),
+            other => ServiceError::BadRequest(other.to_string()),
+        }
+    }
+}
+
+impl From<RegistryError> for ServiceError {
+    fn from(err: RegistryError) -> Self {
+        match err {
+            RegistryError::UnknownChain(chain_id) => ServiceError::UnknownChain(chain_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    pub status: &'static str,
+    pub chains: Vec<ChainHealth>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainHealth {
+    pub chain_id: ChainId,
+    pub block_number: u64,
+    pub tokens: usize,
+    pub pools: usize,
+}
+
+/// Outcome of one quote of a batch: either the quote or why there is none.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum BatchItem {
+    Quote(Box<Quote>),
+    Error { error: String },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArbitrageRequest {
+    #[serde(default = "mainnet")]
+    pub chain_id: ChainId,
+    /// Start tokens to scan; every token of the graph when absent.
+    #[serde(default)]
+    pub tokens: Option<Vec<Token>>,
+    #[serde(default = "one", with = "rust_decimal::serde::str")]
+    pub amount_in: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub min_profit: Decimal,
+    #[serde(default)]
+    pub params: ExecutionParams,
+}
+
+fn one() -> Decimal {
+    Decimal::ONE
+}
+
+fn mainnet() -> ChainId {
+    MAINNET
+}
+
+/// New reserves for one pool.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReserveUpdate {
+    pub id: PoolId,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub reserve0: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub reserve1: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolUpdateRequest {
+    #[serde(default = "mainnet")]
+    pub chain_id: ChainId,
+    /// Block the new reserves belong to; the current block number is kept when absent.
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    pub updates: Vec<ReserveUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolUpdateResponse {
+    pub block_number: u64,
+    pub updated: usize,
+}
+
+/// Transport independent request handling shared by the HTTP and JSON-RPC front ends.
+///
+/// Every request names a chain (mainnet by default) and is served from that
+/// chain's graph only. Quotes are computed against the snapshot that is current
+/// when the request starts, so reserve updates published meanwhile never mix
+/// into a search.
+pub struct QuoteService<R> {
+    router: R,
+    registry: GraphRegistry,
+}
+
+impl<R: RoutingStrategy + Sync> QuoteService<R> {
+    /// A service for the single chain of the graph in `store`.
+    pub fn new(router: R, store: Arc<GraphStore>) -> Self {
+        Self::with_registry(router, GraphRegistry::new().with_store(store))
+    }
+
+    pub fn with_registry(router: R, registry: GraphRegistry) -> Self {
+        Self { router, registry }
+    }
+
+    pub fn registry(&self) -> &GraphRegistry {
+        &self.registry
+    }
+
+    pub fn store(&self, chain_id: ChainId) -> Result<&Arc<GraphStore>, ServiceError> {
+        Ok(self.registry.store(chain_id)?)
+    }
+
+    pub fn health(&self) -> Health {
+        let chains = self
+            .registry
+            .stores()
+            .map(|store| {
+                let graph = store.snapshot();
+                ChainHealth {
+                    chain_id: graph.chain_id,
+                    block_number: graph.block_number,
+                    tokens: graph.adjacency.len(),
+                    pools: graph.pools.len(),
+                }
+            })
+            .collect();
+        Health {
+            status: "ok",
+            chains,
+        }
+    }
+
+    pub fn quote(&self, request: &QuoteRequest) -> Result<Quote, ServiceError> {
+        let graph = self.registry.snapshot(request.chain_id)?;
+        self.quote_at(&graph, request)
+    }
+
+    /// Quotes `request` against a given snapshot rather than the latest one.
+    pub fn quote_at(&self, graph: &Graph, request: &QuoteRequest) -> Result<Quote, ServiceError> {
+        request.validate().map_err(ServiceError::BadRequest)?;
+        if graph.chain_id != request.chain_id {
+            return Err(ServiceError::UnknownChain(request.chain_id));
+        }
+        quote(&self.router, graph, request).ok_or_else(|| ServiceError::NoRoute {
+            from: request.from.clone(),
+            to: request.to.clone(),
+        })
+    }
+
+    /// Quotes `requests` in parallel, all requests of a chain against the same snapshot.
+    pub fn quote_batch(&self, requests: &[QuoteRequest]) -> Vec<BatchItem> {
+        let graphs: HashMap<ChainId, Arc<Graph>> = self
+            .registry
+            .stores()
+            .map(|store| {
+                let graph = store.snapshot();
+                (graph.chain_id, graph)
+            })
+            .collect();
+        requests
+            .into_par_iter()
+            .map(|request| {
+                let graph = graphs
+                    .get(&request.chain_id)
+                    .ok_or(ServiceError::UnknownChain(request.chain_id));
+                match graph.and_then(|graph| self.quote_at(graph, request)) {
+                    Ok(found) => BatchItem::Quote(Box::new(found)),
+                    Err(err) => BatchItem::Error {
+                        error: err.to_string(),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    pub fn arbitrage(&self, request: &ArbitrageRequest) -> Result<Vec<Opportunity>, ServiceError> {
+        validate_amount(request.amount_in).map_err(ServiceError::BadRequest)?;
+        let graph = self.registry.snapshot(request.chain_id)?;
+        let tokens = match &request.tokens {
+            Some(tokens) => tokens.clone(),
+            None => {
+                let mut tokens: Vec<Token> = graph.adjacency.keys().cloned().collect();
+                tokens.sort_by_key(|token| token.0);
+                tokens
+            }
+        };
+        Ok(scan_arbitrage(
+            &graph,
+            &tokens,
+            request.amount_in,
+            request.min_profit,
+            &request.params,
+        ))
+    }
+
+    /// Applies every update of `request` and publishes them as one new snapshot.
+    ///
+    /// Nothing is published if any pool is unknown or a reserve is negative.
+    pub fn update_pools(
+        &self,
+        request: &PoolUpdateRequest,
+    ) -> Result<PoolUpdateResponse, ServiceError> {
+        if let Some(update) = request
+            .updates
+            .iter()
+            .find(|update| update.reserve0.is_sign_negative() || update.reserve1.is_sign_negative())
+        {
+            return Err(ServiceError::BadRequest(format!(
+                "negative reserve for pool {}",
+                update.id
+            )));
+        }
+        let store = self.store(request.chain_id)?;
+        let block_number = request.block_number.unwrap_or_else(|| store.block_number());
+        store.update(block_number, |graph| {
+            for update in &request.updates {
+                graph.update_reserves(&update.id, update.reserve0, update.reserve1)?;
+            }
+            Ok::<_, ServiceError>(())
+        })?;
+        Ok(PoolUpdateResponse {
+            block_number,
+            updated: request.updates.len(),
+        })
+    }
+}
@@ -0,0 +1,185 @@
+//! Watched quotes that are re-evaluated on every published graph.
+//!
+//! A subscription reports its current quote when it is created, then only when
+//! a new graph changes its route or output amount.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::adapter::graph::Graph;
+use crate::application::quote::{Quote, QuoteRequest};
+use crate::domain::types::PoolId;
+use crate::port::routing::RoutingStrategy;
+use crate::server::service::QuoteService;
+
+/// Messages sent by a subscriber.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Watches the quote for a pair and size; replaces a subscription with the same id.
+    Subscribe {
+        id: String,
+        #[serde(flatten)]
+        request: Box<QuoteRequest>,
+    },
+    Unsubscribe {
+        id: String,
+    },
+}
+
+/// Messages pushed to a subscriber.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Quote {
+        id: String,
+        block_number: u64,
+        quote: Box<Quote>,
+    },
+    /// The pair is no longer connected within the subscription's limits.
+    NoRoute {
+        id: String,
+        block_number: u64,
+    },
+    Unsubscribed {
+        id: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// What a result is compared on to decide whether it changed.
+#[derive(Debug, Clone, PartialEq)]
+struct Outcome {
+    output_amount: Decimal,
+    pools: Vec<PoolId>,
+}
+
+impl Outcome {
+    fn of(quote: &Quote) -> Self {
+        Self {
+            output_amount: quote.output_amount,
+            pools: quote
+                .route
+                .steps
+                .iter()
+                .map(|step| step.pool.id.clone())
+                .collect(),
+        }
+    }
+}
+
+struct Entry {
+    request: QuoteRequest,
+    /// Last reported result, `None` when "no route" was reported.
+    last: Option<Outcome>,
+}
+
+/// The subscriptions of one client, keyed by the client's id.
+#[derive(Default)]
+pub struct Subscriptions {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Subscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies `message` and returns the reply, quoting new subscriptions against
+    /// the latest graph of their chain.
+    pub fn handle<R: RoutingStrategy + Sync>(
+        &mut self,
+        service: &QuoteService<R>,
+        message: ClientMessage,
+    ) -> ServerMessage {
+        match message {
+            ClientMessage::Subscribe { id, request } => {
+                let graph = match service.store(request.chain_id) {
+                    Ok(store) => store.snapshot(),
+                    Err(err) => {
+                        return ServerMessage::Error {
+                            message: err.to_string(),
+                        };
+                    }
+                };
+                let (last, reply) = evaluate(service, &graph, &id, &request);
+                self.entries.insert(
+                    id,
+                    Entry {
+                        request: *request,
+                        last,
+                    },
+                );
+                reply
+            }
+            ClientMessage::Unsubscribe { id } => match self.entries.remove(&id) {
+                Some(_) => ServerMessage::Unsubscribed { id },
+                None => ServerMessage::Error {
+                    message: format!("no subscription {}", id),
+                },
+            },
+        }
+    }
+
+    /// Re-quotes every subscription on the chain of `graph` against it and returns
+    /// those whose result changed.
+    ///
+    /// Each subscription is searched again, not re-priced along its last route, so
+    /// it moves to pools that the update made better; a `CachedRouter` only reuses
+    /// paths found on this version of the graph.
+    pub fn refresh<R: RoutingStrategy + Sync>(
+        &mut self,
+        service: &QuoteService<R>,
+        graph: &Graph,
+    ) -> Vec<ServerMessage> {
+        self.entries
+            .iter_mut()
+            .filter(|(_, entry)| entry.request.chain_id == graph.chain_id)
+            .filter_map(|(id, entry)| {
+                let (last, reply) = evaluate(service, graph, id, &entry.request);
+                (last != entry.last).then(|| {
+                    entry.last = last;
+                    reply
+                })
+            })
+            .collect()
+    }
+}
+
+fn evaluate<R: RoutingStrategy + Sync>(
+    service: &QuoteService<R>,
+    graph: &Graph,
+    id: &str,
+    request: &QuoteRequest,
+) -> (Option<Outcome>, ServerMessage) {
+    let block_number = graph.block_number;
+    match service.quote_at(graph, request) {
+        Ok(quote) => (
+            Some(Outcome::of(&quote)),
+            ServerMessage::Quote {
+                id: id.to_string(),
+                block_number,
+                quote: Box::new(quote),
+            },
+        ),
+        Err(_) => (
+            None,
+            ServerMessage::NoRoute {
+                id: id.to_string(),
+                block_number,
+            },
+        ),
+    }
+}
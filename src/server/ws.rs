@@ -0,0 +1,272 @@
+//! Just enough WebSocket (RFC 6455) for quote subscriptions: the upgrade
+//! handshake, unmasked server frames, fragmented client messages, ping and close.
+
+use std::io::{BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, mpsc};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::domain::types::ChainId;
+use crate::port::routing::RoutingStrategy;
+use crate::server::http::{MAX_BODY_BYTES, Request, Response};
+use crate::server::service::QuoteService;
+use crate::server::subscription::{ClientMessage, ServerMessage, Subscriptions};
+
+/// Appended to the client key before hashing it into `Sec-WebSocket-Accept`.
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const CONTINUATION: u8 = 0x0;
+const TEXT: u8 = 0x1;
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close,
+}
+
+/// `true` when `request` asks to switch the connection to WebSocket.
+pub fn is_upgrade(request: &Request) -> bool {
+    request
+        .header("upgrade")
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// `Sec-WebSocket-Accept` value for the client's `Sec-WebSocket-Key`.
+pub fn accept_key(key: &str) -> String {
+    let digest = sha1_smol::Sha1::from(format!("{}{}", key, ACCEPT_GUID)).digest();
+    STANDARD.encode(digest.bytes())
+}
+
+/// The `101 Switching Protocols` response head for `request`.
+pub fn handshake(request: &Request) -> Result<String, Response> {
+    if request.method != "GET" {
+        return Err(Response::error(405, "WebSocket upgrades must use GET"));
+    }
+    if request.header("sec-websocket-version") != Some("13") {
+        return Err(Response::error(400, "unsupported WebSocket version"));
+    }
+    let Some(key) = request.header("sec-websocket-key") else {
+        return Err(Response::error(400, "missing Sec-WebSocket-Key"));
+    };
+    Ok(format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    ))
+}
+
+/// Writes one unfragmented, unmasked frame.
+pub fn write_frame(out: &mut impl Write, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut head = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => head.push(len as u8),
+        len @ 126..=0xffff => {
+            head.push(126);
+            head.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            head.push(127);
+            head.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.write_all(&head)?;
+    out.write_all(payload)?;
+    out.flush()
+}
+
+/// Reads client messages, reassembling fragmented ones.
+pub struct MessageReader<R> {
+    inner: R,
+    /// Opcode and payload received so far of a fragmented message.
+    partial: Option<(u8, Vec<u8>)>,
+}
+
+impl<R: Read> MessageReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            partial: None,
+        }
+    }
+
+    /// Reads the next complete message; control frames may arrive between fragments.
+    pub fn read_message(&mut self) -> std::io::Result<Message> {
+        loop {
+            let (fin, opcode, payload) = self.read_frame()?;
+            let (opcode, payload) = match opcode {
+                CLOSE => return Ok(Message::Close),
+                PING => return Ok(Message::Ping(payload)),
+                PONG => return Ok(Message::Pong(payload)),
+                CONTINUATION => {
+                    let Some((opcode, mut buffer)) = self.partial.take() else {
+                        return Err(invalid("continuation without a message"));
+                    };
+                    if buffer.len() + payload.len() > MAX_BODY_BYTES {
+                        return Err(invalid("message too large"));
+                    }
+                    buffer.extend_from_slice(&payload);
+                    (opcode, buffer)
+                }
+                TEXT | BINARY if self.partial.is_none() => (opcode, payload),
+                TEXT | BINARY => return Err(invalid("new message before the last one ended")),
+                _ => return Err(invalid("unknown opcode")),
+            };
+            if !fin {
+                self.partial = Some((opcode, payload));
+                continue;
+            }
+            return match opcode {
+                TEXT => String::from_utf8(payload)
+                    .map(Message::Text)
+                    .map_err(|_| invalid("text message is not UTF-8")),
+                _ => Ok(Message::Binary(payload)),
+            };
+        }
+    }
+
+    fn read_frame(&mut self) -> std::io::Result<(bool, u8, Vec<u8>)> {
+        let mut head = [0u8; 2];
+        self.inner.read_exact(&mut head)?;
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0f;
+        if head[1] & 0x80 == 0 {
+            return Err(invalid("client frames must be masked"));
+        }
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut len = [0u8; 2];
+                self.inner.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as u64
+            }
+            127 => {
+                let mut len = [0u8; 8];
+                self.inner.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => len as u64,
+        };
+        if len > MAX_BODY_BYTES as u64 {
+            return Err(invalid("message too large"));
+        }
+        let mut mask = [0u8; 4];
+        self.inner.read_exact(&mut mask)?;
+        let mut payload = vec![0u8; len as usize];
+        self.inner.read_exact(&mut payload)?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+        Ok((fin, opcode, payload))
+    }
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+enum Event {
+    Message(Message),
+    /// A new graph was published for a chain.
+    Published(ChainId),
+    Closed,
+}
+
+/// Completes the upgrade of `request` and runs a subscription session until the
+/// client disconnects.
+///
+/// Client messages and graph publications are funnelled into one channel so
+/// that the session thread is the only writer to the socket.
+pub fn serve<R: RoutingStrategy + Send + Sync + 'static>(
+    service: Arc<QuoteService<R>>,
+    request: &Request,
+    reader: BufReader<TcpStream>,
+) -> std::io::Result<()> {
+    let mut writer = reader.get_ref().try_clone()?;
+    match handshake(request) {
+        Ok(head) => writer.write_all(head.as_bytes())?,
+        Err(response) => return response.write_to(&mut writer),
+    }
+
+    let (events, received) = mpsc::channel();
+    for store in service.registry().stores() {
+        let published = events.clone();
+        store.on_publish(move |graph| published.send(Event::Published(graph.chain_id)).is_ok());
+    }
+    let reading = std::thread::spawn(move || {
+        let mut reader = MessageReader::new(reader);
+        loop {
+            match reader.read_message() {
+                Ok(message) => {
+                    if events.send(Event::Message(message)).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => {
+                    let _ = events.send(Event::Closed);
+                    break;
+                }
+            }
+        }
+    });
+
+    let result = run_session(&service, &received, &mut writer);
+    let _ = write_frame(&mut writer, CLOSE, &[]);
+    // Unblocks the reading thread
+    let _ = writer.shutdown(Shutdown::Both);
+    let _ = reading.join();
+    result
+}
+
+fn run_session<R: RoutingStrategy + Sync>(
+    service: &QuoteService<R>,
+    events: &mpsc::Receiver<Event>,
+    writer: &mut TcpStream,
+) -> std::io::Result<()> {
+    let mut subscriptions = Subscriptions::new();
+    for event in events {
+        match event {
+            Event::Message(Message::Text(text)) => {
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(message) => subscriptions.handle(service, message),
+                    Err(err) => ServerMessage::Error {
+                        message: format!("invalid message: {}", err),
+                    },
+                };
+                send(writer, &reply)?;
+            }
+            Event::Message(Message::Binary(_)) => send(
+                writer,
+                &ServerMessage::Error {
+                    message: "expected a text message".to_string(),
+                },
+            )?,
+            Event::Message(Message::Ping(payload)) => write_frame(writer, PONG, &payload)?,
+            Event::Message(Message::Pong(_)) => {}
+            Event::Published(chain_id) => {
+                // Always re-quote against the latest graph, which may be newer than
+                // the publication that triggered this event
+                let Ok(store) = service.store(chain_id) else {
+                    continue;
+                };
+                let graph = store.snapshot();
+                for message in subscriptions.refresh(service, &graph) {
+                    send(writer, &message)?;
+                }
+            }
+            Event::Message(Message::Close) | Event::Closed => break,
+        }
+    }
+    Ok(())
+}
+
+fn send(writer: &mut impl Write, message: &ServerMessage) -> std::io::Result<()> {
+    let text = serde_json::to_string(message).map_err(std::io::Error::other)?;
+    write_frame(writer, TEXT, text.as_bytes())
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::adapter::graph_registry::{GraphRegistry, RegistryError};
+use crate::adapter::loader::parse_json;
+use crate::application::quote::{QuoteRequest, quote};
+use crate::domain::types::*;
+use crate::port::routing::DefaultRouter;
+
+const ARBITRUM: ChainId = 42161;
+
+fn pool(chain_id: ChainId, reserve1: rust_decimal::Decimal) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId("eth-usdc"),
+        token0: Token("ETH"),
+        token1: Token("USDC"),
+        reserve0: dec!(1000),
+        reserve1,
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id,
+    })
+}
+
+fn request(chain_id: ChainId) -> QuoteRequest {
+    QuoteRequest {
+        chain_id,
+        side: Side::Sell,
+        from: Token("ETH"),
+        to: Token("USDC"),
+        amount_in: dec!(1),
+        params: ExecutionParams::default(),
+    }
+}
+
+#[test]
+fn test_requests_are_routed_on_their_own_chain() {
+    // Same pool ID and tokens on both chains, different prices
+    let registry = GraphRegistry::new()
+        .with_graph(Graph::new(&[pool(MAINNET, dec!(2000000))]))
+        .with_graph(Graph::new(&[pool(ARBITRUM, dec!(1000000))]));
+    assert_eq!(registry.chains().collect::<Vec<_>>(), [MAINNET, ARBITRUM]);
+
+    let mainnet = registry.snapshot(MAINNET).unwrap();
+    let arbitrum = registry.snapshot(ARBITRUM).unwrap();
+    let on_mainnet = quote(&DefaultRouter, &mainnet, &request(MAINNET)).unwrap();
+    let on_arbitrum = quote(&DefaultRouter, &arbitrum, &request(ARBITRUM)).unwrap();
+    assert_eq!(on_arbitrum.chain_id, ARBITRUM);
+    assert_eq!(on_arbitrum.route.steps[0].pool.chain_id, ARBITRUM);
+    assert!(on_mainnet.output_amount > on_arbitrum.output_amount * dec!(1.9));
+
+    // A graph never answers for another chain
+    assert!(quote(&DefaultRouter, &mainnet, &request(ARBITRUM)).is_none());
+    assert_eq!(
+        registry.snapshot(10).unwrap_err(),
+        RegistryError::UnknownChain(10)
+    );
+    assert_eq!(
+        registry.publish(Graph::empty(10)),
+        Err(RegistryError::UnknownChain(10))
+    );
+}
+
+#[test]
+fn test_pools_of_other_chains_are_rejected() {
+    let mut graph = Graph::new(&[pool(MAINNET, dec!(2000000))]);
+    let foreign = Arc::new(Pool {
+        id: PoolId("eth-dai"),
+        token1: Token("DAI"),
+        ..(*pool(ARBITRUM, dec!(1000000))).clone()
+    });
+    assert_eq!(
+        graph.insert_pool(Arc::clone(&foreign)),
+        Err(GraphError::ChainMismatch {
+            pool: PoolId("eth-dai"),
+            expected: MAINNET,
+            actual: ARBITRUM,
+        })
+    );
+    assert!(Graph::try_new(&[pool(MAINNET, dec!(2000000)), foreign]).is_err());
+    assert_eq!(graph.pools.len(), 1);
+
+    let dump = concat!(
+        r#"{"id": "a", "token0": {"symbol": "ETH"}, "token1": {"symbol": "USDC"}, "reserve0": "1", "reserve1": "2", "fee_bps": 30, "chain_id": 42161}"#,
+        "\n",
+        r#"{"id": "b", "token0": {"symbol": "ETH"}, "token1": {"symbol": "DAI"}, "reserve0": "1", "reserve1": "2", "fee_bps": 30}"#,
+    );
+    let err = parse_json(dump).unwrap_err();
+    assert!(err.to_string().starts_with("line 2:"), "{}", err);
+    let first_line = dump.lines().next().unwrap();
+    assert_eq!(
+        parse_json(first_line).unwrap().graph().unwrap().chain_id,
+        ARBITRUM
+    );
+}
@@ -0,0 +1,105 @@
+use rust_decimal::{Decimal, dec};
+
+use crate::cli::args::{Cli, Command, Format};
+use crate::cli::{CliError, run};
+use crate::domain::types::*;
+
+fn run_cli(args: &[&str]) -> Result<String, CliError> {
+    let mut out = Vec::new();
+    run(args.iter().map(|arg| arg.to_string()), &mut out)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_parse_quote_arguments() {
+    let cli = Cli::parse(
+        [
+            "quote", "ETH", "USDC", "2.5", "--side", "sell", "--json", "--algo", "a_star",
+        ]
+        .map(String::from),
+    )
+    .unwrap();
+    assert_eq!(cli.format, Format::Json);
+    let Command::Quote(quote) = cli.command else {
+        panic!("expected a quote command");
+    };
+    assert_eq!((quote.from, quote.to), (Token("ETH"), Token("USDC")));
+    assert_eq!(quote.amount, dec!(2.5));
+    assert_eq!(quote.side, Side::Sell);
+    assert_eq!(quote.algo, RoutingAlgo::AStar);
+    assert_eq!(quote.max_hops, 4);
+
+    for args in [
+        &["quote", "ETH", "USDC"][..],
+        &["quote", "ETH", "USDC", "ten"],
+        &["quote", "ETH", "USDC", "0"],
+        &["quote", "ETH", "USDC", "-1"],
+        &["quote", "ETH", "USDC", "79228162514264337593543950335"],
+        &["quote", "ETH", "USDC", "1", "--side", "buy"],
+        &["arb", "--amount", "0"],
+        &["graph"],
+        &[],
+    ] {
+        let err = Cli::parse(args.iter().map(|arg| arg.to_string())).unwrap_err();
+        assert_eq!(err.exit_code(), 2, "{args:?}");
+    }
+}
+
+#[test]
+fn test_quote_outputs_json_and_fails_without_route() {
+    let pools = concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_pools.csv");
+    let output = run_cli(&[
+        "--pools",
+        pools,
+        "--json",
+        "quote",
+        "ETH",
+        "USDC",
+        "10",
+        "--slippage",
+        "100",
+    ])
+    .unwrap();
+    let quote: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(quote["from"], "ETH");
+    assert_eq!(quote["route"]["steps"][0]["from"], "ETH");
+    let output_amount: Decimal = quote["output_amount"].as_str().unwrap().parse().unwrap();
+    let min_output: Decimal = quote["min_output_amount"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(min_output, output_amount * dec!(0.99));
+
+    let args = [
+        "--pools",
+        pools,
+        "--min-reserve",
+        "2000",
+        "quote",
+        "ETH",
+        "USDC",
+        "10",
+    ];
+    let human = run_cli(&args).unwrap();
+    assert!(human.contains("→ Liquidity: "), "{human}");
+    let quote: serde_json::Value =
+        serde_json::from_str(&run_cli(&[&args[..], &["--json"]].concat()).unwrap()).unwrap();
+    assert_eq!(quote["pruned"]["checked"], 11);
+    assert!(
+        quote["pruned"]["pruned"]
+            .as_object()
+            .is_some_and(|pruned| !pruned.is_empty())
+    );
+
+    let err = run_cli(&["--pools", pools, "quote", "ETH", "FOO", "10"]).unwrap_err();
+    assert!(matches!(err, CliError::NoRoute { .. }));
+    assert_eq!(err.exit_code(), 1);
+
+    let err = run_cli(&["--pools", "missing.csv", "graph", "stats"]).unwrap_err();
+    assert_eq!(err.exit_code(), 3);
+
+    let stats = run_cli(&["graph", "stats", "--json"]).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(&stats).unwrap();
+    assert_eq!(stats["pools"], 11);
+}
@@ -1,40 +1,57 @@
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use rust_decimal::dec;
+use rust_decimal::{Decimal, dec};
 
 use crate::adapter::graph::Graph;
+use crate::application::algos::circular::circular_cancellable;
+use crate::application::quote::scan_arbitrage_cancellable;
+use crate::application::search_control::CancellationToken;
 use crate::domain::types::*;
+use crate::infra::route_cache::{CacheStats, CachedRouter};
 use crate::port::routing::{DefaultRouter, RoutingStrategy};
 use std::sync::Arc;
+use std::time::Duration;
 
 fn setup_graph() -> (Graph, Vec<Arc<Pool>>) {
     let pools = vec![
         Pool {
+            id: PoolId("eth-usdc-1"),
             token0: Token("ETH"),
             token1: Token("USDC"),
             reserve0: dec!(1000),
             reserve1: dec!(1000000),
             fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
         },
         Pool {
+            id: PoolId("usdc-dai-1"),
             token0: Token("USDC"),
             token1: Token("DAI"),
             reserve0: dec!(1000000),
             reserve1: dec!(1000000),
             fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
         },
         Pool {
+            id: PoolId("dai-wbtc-1"),
             token0: Token("DAI"),
             token1: Token("WBTC"),
             reserve0: dec!(1000000),
             reserve1: dec!(50),
             fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
         },
         Pool {
+            id: PoolId("eth-wbtc-1"),
             token0: Token("ETH"),
             token1: Token("WBTC"),
             reserve0: dec!(1000),
             reserve1: dec!(50),
             fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
         },
     ];
 
@@ -55,6 +72,7 @@ fn test_direct_and_indirect_route() {
         },
         algo: RoutingAlgo::Auto,
         max_hops: 4,
+        ..Default::default()
     };
 
     let route = router.compute_route(Side::Buy, &graph, &from, &to, dec!(10), params);
@@ -80,6 +98,7 @@ fn test_slippage_enforced() {
         },
         algo: RoutingAlgo::Dijkstra,
         max_hops: 4,
+        ..Default::default()
     };
 
     if let Some((_algo, route)) =
@@ -92,3 +111,358 @@ fn test_slippage_enforced() {
         panic!("No route found with slippage tolerance");
     }
 }
+
+/// A graph where the best way into X has already visited Y, the only way on
+/// from X: a width of 1 keeps that dead end and misses SRC-Z-X-Y-DST.
+fn beam_trap_graph() -> Graph {
+    let pool = |id, token0, token1, reserve1| {
+        Arc::new(Pool {
+            id: PoolId(id),
+            token0: Token(token0),
+            token1: Token(token1),
+            reserve0: dec!(1000000000),
+            reserve1,
+            fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
+        })
+    };
+    Graph::new(&[
+        pool("src-y", "SRC", "Y", dec!(1000000000)),
+        pool("y-x", "Y", "X", dec!(3000000000)),
+        pool("src-z", "SRC", "Z", dec!(1000000000)),
+        pool("z-x", "Z", "X", dec!(2000000000)),
+        pool("x-y", "X", "Y", dec!(3000000000)),
+        pool("y-dst", "Y", "DST", dec!(1000000000)),
+    ])
+}
+
+#[test]
+fn test_beam_search_width() {
+    let graph = beam_trap_graph();
+    let from = Token("SRC");
+    let to = Token("DST");
+    let input = dec!(10);
+    let pool_ids = |route: &Route| route.steps.iter().map(|s| s.pool.id.0).collect::<Vec<_>>();
+
+    let narrow = ExecutionParams {
+        algo: RoutingAlgo::Beam,
+        beam_width: 1,
+        ..Default::default()
+    };
+    let (algo, narrow_route) = DefaultRouter
+        .compute_route(Side::Sell, &graph, &from, &to, input, narrow.clone())
+        .expect("beam search should find a route");
+    assert_eq!(algo, RoutingAlgo::Beam);
+    assert_eq!(pool_ids(&narrow_route), ["src-y", "y-dst"]);
+
+    for beam_width in [2, 16] {
+        let wide = ExecutionParams {
+            beam_width,
+            ..narrow.clone()
+        };
+        let (_, wide_route) = DefaultRouter
+            .compute_route(Side::Sell, &graph, &from, &to, input, wide)
+            .expect("beam search should find a route");
+        assert_eq!(pool_ids(&wide_route), ["src-z", "z-x", "x-y", "y-dst"]);
+        assert!(wide_route.output_amount > dec!(5) * narrow_route.output_amount);
+    }
+}
+
+#[test]
+fn test_search_budget_returns_partial_route() {
+    let (graph, _) = setup_graph();
+    let from = Token("ETH");
+    let to = Token("WBTC");
+    let input = dec!(10);
+
+    let limited = ExecutionParams {
+        algo: RoutingAlgo::Bfs,
+        budget: SearchBudget {
+            max_expansions: Some(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (_, route) = DefaultRouter
+        .compute_route(Side::Buy, &graph, &from, &to, input, limited.clone())
+        .expect("a route to the target was reached before the budget ran out");
+    assert!(route.partial);
+    assert_eq!(route.steps.last().unwrap().to, to);
+
+    let unlimited = ExecutionParams {
+        budget: SearchBudget::default(),
+        ..limited
+    };
+    let (_, route) = DefaultRouter
+        .compute_route(Side::Buy, &graph, &from, &to, input, unlimited)
+        .unwrap();
+    assert!(!route.partial);
+}
+
+#[test]
+fn test_expired_deadline_flags_auto_result_partial() {
+    let (graph, _) = setup_graph();
+    let params = ExecutionParams {
+        budget: SearchBudget {
+            max_duration: Some(Duration::ZERO),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let (algo, route) = DefaultRouter
+        .compute_route(
+            Side::Buy,
+            &graph,
+            &Token("ETH"),
+            &Token("WBTC"),
+            dec!(10),
+            params,
+        )
+        .expect("the direct pool is always quoted");
+    assert_eq!(algo, RoutingAlgo::BestDirect);
+    assert!(route.partial);
+}
+
+#[test]
+fn test_cancelled_search_reports_cancellation() {
+    let (graph, _) = setup_graph();
+    let from = Token("ETH");
+    let to = Token("WBTC");
+    let router = DefaultRouter;
+    let params = ExecutionParams::default();
+
+    let token = CancellationToken::new();
+    let outcome = router.compute_route_cancellable(
+        Side::Buy,
+        &graph,
+        &from,
+        &to,
+        dec!(10),
+        params.clone(),
+        &token,
+    );
+    assert!(matches!(outcome, RouteOutcome::Found(..)));
+
+    token.cancel();
+    for algo in [RoutingAlgo::Auto, RoutingAlgo::AStar, RoutingAlgo::Dfs] {
+        let outcome = router.compute_route_cancellable(
+            Side::Buy,
+            &graph,
+            &from,
+            &to,
+            dec!(10),
+            ExecutionParams {
+                algo,
+                ..params.clone()
+            },
+            &token,
+        );
+        assert!(
+            matches!(outcome, RouteOutcome::Cancelled),
+            "{algo:?} ignored cancellation"
+        );
+    }
+}
+
+/// Every pair of `n` tokens shares a pool, so long routes have a huge search space.
+fn dense_graph(n: usize) -> Graph {
+    let token = |i: usize| Token(intern(&format!("T{i}")));
+    let pools: Vec<_> = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .map(|(i, j)| {
+            Arc::new(Pool {
+                id: PoolId(intern(&format!("t{i}-t{j}"))),
+                token0: token(i),
+                token1: token(j),
+                reserve0: Decimal::from(1000 + (i * 7 + j * 13) % 97),
+                reserve1: Decimal::from(1000 + (i * 11 + j * 5) % 89),
+                fee_bps: dec!(30),
+                venue: Venue::UNISWAP,
+                chain_id: MAINNET,
+            })
+        })
+        .collect();
+    Graph::new(&pools)
+}
+
+#[test]
+fn test_search_stops_when_cancelled_midway() {
+    let graph = dense_graph(30);
+    let from = Token("T0");
+    // Unreachable, so nothing short of cancellation ends the search early
+    let to = Token("NOWHERE");
+    let params = ExecutionParams {
+        max_hops: 4,
+        ..ExecutionParams::default()
+    };
+    // Searches check the token once per expansion, so this cancels the 100th
+    let midway = || CancellationToken::cancelled_on_check(100);
+
+    for algo in [RoutingAlgo::Bfs, RoutingAlgo::Dijkstra] {
+        let params = ExecutionParams {
+            algo,
+            ..params.clone()
+        };
+        let unlimited = DefaultRouter.compute_route_cancellable(
+            Side::Sell,
+            &graph,
+            &from,
+            &to,
+            dec!(1),
+            params.clone(),
+            &CancellationToken::new(),
+        );
+        assert!(matches!(unlimited, RouteOutcome::NoRoute), "{algo:?}");
+
+        let token = midway();
+        let outcome = DefaultRouter.compute_route_cancellable(
+            Side::Sell,
+            &graph,
+            &from,
+            &to,
+            dec!(1),
+            params,
+            &token,
+        );
+        assert!(
+            matches!(outcome, RouteOutcome::Cancelled),
+            "{algo:?} ignored cancellation"
+        );
+    }
+
+    let token = midway();
+    let outcome = circular_cancellable(
+        Side::Sell,
+        &graph,
+        &from,
+        &from,
+        dec!(1),
+        params.clone(),
+        &token,
+    );
+    assert!(matches!(outcome, RouteOutcome::Cancelled));
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let tokens = [from.clone()];
+    assert!(
+        scan_arbitrage_cancellable(&graph, &tokens, dec!(1), dec!(0), &params, &token).is_none()
+    );
+}
+
+#[test]
+fn test_route_cache_reprices_and_invalidates() {
+    let (graph, _) = setup_graph();
+    let router = CachedRouter::new(DefaultRouter);
+    let from = Token("ETH");
+    let to = Token("WBTC");
+    let params = ExecutionParams::default();
+
+    let (_, first) = router
+        .compute_route(Side::Buy, &graph, &from, &to, dec!(10), params.clone())
+        .unwrap();
+    assert_eq!(router.stats(), CacheStats { hits: 0, misses: 1 });
+
+    // 12 shares the [8, 16) bucket with 10: served from cache, re-priced
+    let (_, cached) = router
+        .compute_route(Side::Buy, &graph, &from, &to, dec!(12), params.clone())
+        .unwrap();
+    let (_, fresh) = DefaultRouter
+        .compute_route(Side::Buy, &graph, &from, &to, dec!(12), params.clone())
+        .unwrap();
+    assert_eq!(router.stats(), CacheStats { hits: 1, misses: 1 });
+    assert_eq!(cached.output_amount, fresh.output_amount);
+    assert!(cached.output_amount > first.output_amount);
+
+    // Changing a pool, on the cached path or not, forces a new search
+    let changed_id = first.steps[0].pool.id.clone();
+    let updated = |id: &PoolId| {
+        let mut graph = graph.clone();
+        let pool = graph.pool(id).unwrap();
+        let (reserve0, reserve1) = (pool.reserve0, pool.reserve1 * dec!(2));
+        graph.update_reserves(id, reserve0, reserve1).unwrap();
+        graph
+    };
+    for (id, misses) in [(&changed_id, 2), (&PoolId("usdc-dai-1"), 3)] {
+        router
+            .compute_route(
+                Side::Buy,
+                &updated(id),
+                &from,
+                &to,
+                dec!(10),
+                params.clone(),
+            )
+            .unwrap();
+        assert_eq!(router.stats(), CacheStats { hits: 1, misses });
+    }
+}
+
+#[test]
+fn test_route_cache_keeps_restricted_paths_apart() {
+    let (graph, _) = setup_graph();
+    let router = CachedRouter::new(DefaultRouter);
+    let from = Token("ETH");
+    let to = Token("WBTC");
+    let unrestricted = ExecutionParams::default();
+    let restricted = [
+        ExecutionParams {
+            excluded_pools: Some(Arc::new([PoolId("eth-wbtc-1")].into())),
+            ..unrestricted.clone()
+        },
+        ExecutionParams {
+            allowed_pools: Some(Arc::new(
+                [
+                    PoolId("eth-usdc-1"),
+                    PoolId("usdc-dai-1"),
+                    PoolId("dai-wbtc-1"),
+                ]
+                .into(),
+            )),
+            max_hops: 3,
+            ..unrestricted.clone()
+        },
+    ];
+
+    for params in restricted {
+        router.clear();
+        let hits = router.stats().hits;
+        let (_, detour) = router
+            .compute_route(Side::Sell, &graph, &from, &to, dec!(10), params.clone())
+            .unwrap();
+        // The restricted path is allowed without restrictions, but not the best one
+        assert!(unrestricted.allows_steps(&detour.steps));
+
+        let (_, cached) = router
+            .compute_route(
+                Side::Sell,
+                &graph,
+                &from,
+                &to,
+                dec!(10),
+                unrestricted.clone(),
+            )
+            .unwrap();
+        let (_, fresh) = DefaultRouter
+            .compute_route(
+                Side::Sell,
+                &graph,
+                &from,
+                &to,
+                dec!(10),
+                unrestricted.clone(),
+            )
+            .unwrap();
+        assert!(fresh.output_amount > detour.output_amount);
+        assert_eq!(cached.output_amount, fresh.output_amount);
+        assert_eq!(router.stats().hits, hits);
+
+        // Identical restrictions still share the cache
+        router
+            .compute_route(Side::Sell, &graph, &from, &to, dec!(10), params)
+            .unwrap();
+        assert_eq!(router.stats().hits, hits + 1);
+    }
+}
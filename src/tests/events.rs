@@ -0,0 +1,261 @@
+use rust_decimal::{Decimal, dec};
+use serde_json::Value;
+
+use crate::adapter::event_applier::{ApplyError, EventApplier};
+use crate::adapter::graph::Graph;
+use crate::adapter::reorg_journal::{ReorgError, ReorgJournal};
+use crate::domain::events::{BlockHash, BlockHeader, PairEvent, RawLog, SYNC_TOPIC};
+use crate::domain::types::*;
+use std::sync::Arc;
+
+const USDC_WETH: &str = "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc";
+const DAI_WETH: &str = "0xa478c2975ab1ea89e8196811f51a7b7ade33eb11";
+
+fn event_graph() -> Graph {
+    let pools =
+        [(USDC_WETH, "USDC", "WETH"), (DAI_WETH, "DAI", "WETH")].map(|(id, token0, token1)| {
+            Arc::new(Pool {
+                id: PoolId(id),
+                token0: Token(token0),
+                token1: Token(token1),
+                reserve0: dec!(0),
+                reserve1: dec!(0),
+                fee_bps: dec!(30),
+                venue: Venue::UNISWAP,
+                chain_id: MAINNET,
+            })
+        });
+    Graph::new(&pools)
+}
+
+fn quantity(value: &Value) -> u64 {
+    u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+    hex::decode(value.as_str().unwrap().trim_start_matches("0x")).unwrap()
+}
+
+/// Reads `eth_getLogs`-shaped entries from a JSON-lines file.
+pub fn read_logs(path: &str) -> Vec<RawLog> {
+    let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path);
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let log: Value = serde_json::from_str(line).unwrap();
+            RawLog {
+                address: log["address"].as_str().unwrap().parse().unwrap(),
+                topics: log["topics"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|topic| bytes(topic).try_into().unwrap())
+                    .collect(),
+                data: bytes(&log["data"]),
+                block_number: quantity(&log["blockNumber"]),
+                log_index: quantity(&log["logIndex"]),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_replay_v2_events_from_jsonl() {
+    let logs = read_logs("src/tests/fixtures/v2_events.jsonl");
+    let mut graph = event_graph();
+    let applier = EventApplier::new().with_decimals(Token("USDC"), 6);
+
+    let applied = applier.apply_all(&mut graph, &logs).unwrap();
+    // Only the Syncs of tracked pairs set reserves; the USDT pair is not tracked
+    assert_eq!(applied, 6);
+
+    let usdc_weth = graph.pool(&PoolId(USDC_WETH)).unwrap();
+    assert_eq!(usdc_weth.reserve0, dec!(30000010));
+    assert_eq!(usdc_weth.reserve1, dec!(10000.003993018960096784));
+
+    let dai_weth = graph.pool(&PoolId(DAI_WETH)).unwrap();
+    assert_eq!(dai_weth.reserve0, dec!(20000500));
+    assert_eq!(dai_weth.reserve1, dec!(6765));
+}
+
+#[test]
+fn test_sync_scales_full_width_reserves() {
+    let mut graph = event_graph();
+    let max = (1u128 << 112) - 1;
+    let applier = EventApplier::new().with_decimals(Token("USDC"), 0);
+
+    // Digits beyond the 96 bits of a `Decimal` mantissa are rounded away
+    applier
+        .apply(&mut graph, &sync_log(DAI_WETH, max, 1 << 96))
+        .unwrap();
+    let dai_weth = graph.pool(&PoolId(DAI_WETH)).unwrap();
+    assert_eq!(dai_weth.reserve0, dec!(5192296858534827.6285304963292));
+    assert_eq!(dai_weth.reserve1, dec!(79228162514.26433759354395034));
+
+    // Without decimals the whole part is out of range
+    assert_eq!(
+        applier
+            .apply(&mut graph, &sync_log(USDC_WETH, max, 1))
+            .unwrap_err(),
+        ApplyError::AmountOutOfRange(PoolId(USDC_WETH))
+    );
+}
+
+#[test]
+fn test_decode_rejects_malformed_logs() {
+    let sync = RawLog {
+        address: USDC_WETH.parse().unwrap(),
+        topics: vec![SYNC_TOPIC],
+        data: vec![0; 64],
+        block_number: 1,
+        log_index: 0,
+    };
+    assert_eq!(
+        PairEvent::decode(&sync),
+        Ok(PairEvent::Sync {
+            reserve0: 0,
+            reserve1: 0
+        })
+    );
+
+    let truncated = RawLog {
+        data: vec![0; 40],
+        ..sync.clone()
+    };
+    assert!(PairEvent::decode(&truncated).is_err());
+
+    let unknown = RawLog {
+        topics: vec![[0xab; 32]],
+        ..sync.clone()
+    };
+    let mut graph = event_graph();
+    assert!(matches!(
+        EventApplier::new().apply(&mut graph, &unknown),
+        Err(ApplyError::Event(_))
+    ));
+}
+
+fn sync_log(pool: &str, reserve0: u128, reserve1: u128) -> RawLog {
+    let mut data = vec![0u8; 64];
+    data[16..32].copy_from_slice(&reserve0.to_be_bytes());
+    data[48..64].copy_from_slice(&reserve1.to_be_bytes());
+    RawLog {
+        address: pool.parse().unwrap(),
+        topics: vec![SYNC_TOPIC],
+        data,
+        block_number: 0,
+        log_index: 0,
+    }
+}
+
+fn block_hash(number: u64, fork: u8) -> BlockHash {
+    let mut hash = [0u8; 32];
+    hash[0] = fork;
+    hash[24..].copy_from_slice(&number.to_be_bytes());
+    hash
+}
+
+/// Block `number` of `fork`. Every block syncs USDC/WETH, even blocks also sync DAI/WETH.
+fn fork_block(number: u64, fork: u8, parent_fork: u8) -> (BlockHeader, Vec<RawLog>) {
+    let header = BlockHeader {
+        number,
+        hash: block_hash(number, fork),
+        parent_hash: block_hash(number - 1, parent_fork),
+    };
+    let seed = u128::from(number) * 1000 + u128::from(fork);
+    let mut logs = vec![sync_log(USDC_WETH, seed * 10, seed)];
+    if number.is_multiple_of(2) {
+        logs.push(sync_log(DAI_WETH, seed * 20, seed * 2));
+    }
+    (header, logs)
+}
+
+fn reserves(graph: &Graph) -> Vec<(Decimal, Decimal)> {
+    [USDC_WETH, DAI_WETH]
+        .iter()
+        .map(|id| {
+            let pool = graph.pool(&PoolId(id)).unwrap();
+            (pool.reserve0, pool.reserve1)
+        })
+        .collect()
+}
+
+#[test]
+fn test_reorg_rolls_back_to_common_ancestor() {
+    for depth in [1u64, 2, 3, 5] {
+        let mut graph = event_graph();
+        let mut journal = ReorgJournal::new(EventApplier::new());
+        for number in 1..=6 {
+            let (header, logs) = fork_block(number, 0, 0);
+            journal.apply_block(&mut graph, header, &logs).unwrap();
+        }
+
+        // The canonical chain forks off after block `6 - depth` and is one block longer
+        let ancestor = 6 - depth;
+        let canonical: Vec<_> = (ancestor + 1..=7)
+            .map(|number| fork_block(number, 1, if number == ancestor + 1 { 0 } else { 1 }))
+            .collect();
+        assert_eq!(
+            journal.reorg(&mut graph, &canonical).unwrap(),
+            depth as usize
+        );
+
+        let mut expected = event_graph();
+        let mut replay = ReorgJournal::new(EventApplier::new());
+        let main_chain = (1..=ancestor).map(|number| fork_block(number, 0, 0));
+        for (header, logs) in main_chain.chain(canonical.iter().cloned()) {
+            replay.apply_block(&mut expected, header, &logs).unwrap();
+        }
+
+        assert_eq!(reserves(&graph), reserves(&expected), "reorg depth {depth}");
+        assert_eq!(graph.block_number, 7);
+        assert_eq!(journal.head().unwrap().hash, block_hash(7, 1));
+    }
+}
+
+#[test]
+fn test_reorg_errors() {
+    let mut graph = event_graph();
+    let mut journal = ReorgJournal::with_max_depth(EventApplier::new(), 3);
+    for number in 1..=6 {
+        let (header, logs) = fork_block(number, 0, 0);
+        journal.apply_block(&mut graph, header, &logs).unwrap();
+    }
+    let before = reserves(&graph);
+
+    // A block that does not extend the head is refused
+    let (orphan, logs) = fork_block(7, 1, 1);
+    assert!(matches!(
+        journal.apply_block(&mut graph, orphan, &logs),
+        Err(ReorgError::NotChild { .. })
+    ));
+
+    // Blocks 1 and 2 fell out of the 3-block journal
+    let too_deep = vec![fork_block(3, 1, 0)];
+    assert_eq!(
+        journal.reorg(&mut graph, &too_deep),
+        Err(ReorgError::UnknownAncestor(block_hash(2, 0)))
+    );
+    assert_eq!(reserves(&graph), before);
+
+    // A canonical block failing midway leaves the old chain in place
+    let (first, first_logs) = fork_block(6, 1, 0);
+    let (mut second, second_logs) = fork_block(7, 1, 1);
+    second.parent_hash = block_hash(6, 2);
+    assert!(matches!(
+        journal.reorg(&mut graph, &[(first, first_logs), (second, second_logs)]),
+        Err(ReorgError::NotChild { .. })
+    ));
+    assert_eq!(reserves(&graph), before);
+    assert_eq!(graph.block_number, 6);
+    assert_eq!(journal.head().unwrap().hash, block_hash(6, 0));
+
+    // Rolling back to the parent of the oldest journaled block is still possible
+    assert_eq!(
+        journal.rollback_to(&mut graph, &block_hash(3, 0)).unwrap(),
+        3
+    );
+    assert!(journal.head().is_none());
+}
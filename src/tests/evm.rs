@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::evm::{EvmExecutor, VenueBytecode, pair_address};
+use crate::adapter::graph::Graph;
+use crate::adapter::pair_math::PairMath;
+use crate::application::verify::{Verification, verify_route};
+use crate::domain::types::*;
+use crate::domain::venue::VenueConfig;
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+use crate::port::verification::ExecutionError;
+use crate::tests::router_v2::{DAI, USDC, WETH, address, info};
+
+fn fixture(hex: &str) -> Vec<u8> {
+    hex::decode(hex.split_whitespace().collect::<String>()).unwrap()
+}
+
+/// Tests run offline, without the deployed contracts, so small hand-assembled
+/// contracts stand in for them:
+///
+/// - the pair answers every call with `getReserves()` unpacked from slot 8;
+/// - the router answers every call as `getAmountsOut(amountIn, [a, b])`: it
+///   derives the pair address like `pairFor` with the Uniswap mainnet factory
+///   and init code hash, calls its `getReserves()`, and returns `[amountIn,
+///   amountIn * 997 * reserveOut / (reserveIn * 1000 + amountIn * 997)]`,
+///   reverting without a reason when the call fails or the output is zero.
+fn stand_in() -> VenueBytecode {
+    VenueBytecode {
+        config: VenueConfig::uniswap_mainnet(),
+        router: fixture(include_str!("fixtures/v2_router_stand_in.hex")),
+        pair: fixture(include_str!("fixtures/v2_pair_stand_in.hex")),
+    }
+}
+
+fn pool(
+    id: &'static str,
+    token0: &'static str,
+    token1: &'static str,
+    reserves: (Decimal, Decimal),
+) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0: reserves.0,
+        reserve1: reserves.1,
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+/// Pools list their tokens by symbol, not in the pairs' address order.
+fn graph() -> Graph {
+    Graph::new(&[
+        pool("weth-usdc", "WETH", "USDC", (dec!(1000), dec!(2000000))),
+        pool("usdc-dai", "USDC", "DAI", (dec!(3000000), dec!(3000000))),
+    ])
+    .with_token_info([
+        info("WETH", address(WETH), 18),
+        info("USDC", address(USDC), 6),
+        info("DAI", address(DAI), 18),
+    ])
+    .unwrap()
+}
+
+fn route(graph: &Graph, amount: Decimal) -> Route {
+    let params = ExecutionParams {
+        allowed_pools: Some(Arc::new([PoolId("weth-usdc"), PoolId("usdc-dai")].into())),
+        max_hops: 2,
+        ..ExecutionParams::default()
+    };
+    let to = Token("DAI");
+    let (_, route) = DefaultRouter
+        .compute_route(Side::Sell, graph, &Token("WETH"), &to, amount, params)
+        .unwrap();
+    assert_eq!(route.steps.len(), 2);
+    route
+}
+
+#[test]
+fn test_pair_address_matches_deployed_pairs() {
+    let uniswap = VenueConfig::uniswap_mainnet();
+    assert_eq!(
+        pair_address(&uniswap, address(USDC), address(WETH)),
+        address("0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc")
+    );
+    assert_eq!(
+        pair_address(&uniswap, address(DAI), address(WETH)),
+        address("0xa478c2975ab1ea89e8196811f51a7b7ade33eb11")
+    );
+}
+
+#[test]
+fn test_evm_executor_runs_router_and_pair_bytecode() {
+    let graph = graph();
+    let executor = EvmExecutor::new().with_venue(stand_in());
+    let route = route(&graph, dec!(1.5));
+
+    let evm = verify_route(&executor, &graph, &route, dec!(1.5)).unwrap();
+    let math = verify_route(&PairMath, &graph, &route, dec!(1.5)).unwrap();
+    let executed = |verification: &Verification| {
+        verification
+            .hops
+            .iter()
+            .map(|hop| hop.executed)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(executed(&evm), executed(&math));
+    assert_eq!(evm.hops[0].executed.scale(), 6);
+    assert!(evm.is_within(dec!(0.000000001)));
+
+    // One wei of WETH is worth less than one raw unit of USDC
+    let err = verify_route(&executor, &graph, &route, dec!(0.000000000000000001)).unwrap_err();
+    assert_eq!(
+        err,
+        ExecutionError::Reverted {
+            pool: PoolId("weth-usdc"),
+            reason: "reverted without a reason".to_string(),
+        }
+    );
+
+    let err = verify_route(&EvmExecutor::new(), &graph, &route, dec!(1.5)).unwrap_err();
+    assert!(matches!(err, ExecutionError::Unsupported(_)), "{err}");
+
+    let unnamed = Graph::new(&[pool(
+        "weth-usdc",
+        "WETH",
+        "USDC",
+        (dec!(1000), dec!(2000000)),
+    )]);
+    let err = verify_route(&executor, &unnamed, &route, dec!(1.5)).unwrap_err();
+    assert!(matches!(err, ExecutionError::Unsupported(_)), "{err}");
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::graph::Graph;
+use crate::application::algos::circular::circular;
+use crate::application::route_engine::execute;
+use crate::domain::types::*;
+
+fn pool(
+    id: &'static str,
+    token0: &'static str,
+    token1: &'static str,
+    reserve0: Decimal,
+    reserve1: Decimal,
+) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0,
+        reserve1,
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+/// ETH → USDC directly or through DAI or WBTC; DAI trades ETH at a premium.
+fn graph() -> Graph {
+    Graph::new(&[
+        pool("eth-usdc", "ETH", "USDC", dec!(1000), dec!(2000000)),
+        pool("eth-dai", "ETH", "DAI", dec!(1000), dec!(2200000)),
+        pool("dai-usdc", "DAI", "USDC", dec!(10000000), dec!(10000000)),
+        pool("eth-wbtc", "ETH", "WBTC", dec!(1000), dec!(50)),
+        pool("wbtc-usdc", "WBTC", "USDC", dec!(50), dec!(2000000)),
+    ])
+}
+
+fn set<T: Eq + std::hash::Hash>(items: impl IntoIterator<Item = T>) -> Option<Arc<HashSet<T>>> {
+    Some(Arc::new(items.into_iter().collect()))
+}
+
+fn pools_used(route: &Route) -> Vec<PoolId> {
+    route
+        .steps
+        .iter()
+        .map(|step| step.pool.id.clone())
+        .collect()
+}
+
+#[test]
+fn test_token_and_pool_filters_apply_to_every_algorithm() {
+    let graph = graph();
+    for algo in [
+        RoutingAlgo::BestDirect,
+        RoutingAlgo::Bfs,
+        RoutingAlgo::Dfs,
+        RoutingAlgo::Dijkstra,
+        RoutingAlgo::AStar,
+        RoutingAlgo::Beam,
+        RoutingAlgo::Auto,
+    ] {
+        let route = |params: ExecutionParams| {
+            let params = ExecutionParams { algo, ..params };
+            execute(
+                Side::Sell,
+                &graph,
+                &Token("ETH"),
+                &Token("USDC"),
+                dec!(1),
+                params,
+            )
+            .map(|(_, route)| pools_used(&route))
+        };
+        // Endpoints are not intermediates, so the direct pool stays usable
+        let direct_only = ExecutionParams {
+            allowed_pools: set([PoolId("eth-usdc")]),
+            allowed_intermediates: set([]),
+            ..Default::default()
+        };
+        assert_eq!(
+            route(direct_only),
+            Some(vec![PoolId("eth-usdc")]),
+            "{:?}",
+            algo
+        );
+        let no_usdc = ExecutionParams {
+            excluded_tokens: set([Token("USDC")]),
+            ..Default::default()
+        };
+        assert_eq!(route(no_usdc), None, "{:?}", algo);
+        if algo == RoutingAlgo::BestDirect {
+            let no_direct = ExecutionParams {
+                excluded_pools: set([PoolId("eth-usdc")]),
+                ..Default::default()
+            };
+            assert_eq!(route(no_direct), None);
+            continue;
+        }
+
+        let through_dai = ExecutionParams {
+            excluded_pools: set([PoolId("eth-usdc")]),
+            allowed_intermediates: set([Token("DAI")]),
+            ..Default::default()
+        };
+        assert_eq!(
+            route(through_dai),
+            Some(vec![PoolId("eth-dai"), PoolId("dai-usdc")]),
+            "{:?}",
+            algo
+        );
+        let not_through_dai = ExecutionParams {
+            excluded_pools: set([PoolId("eth-usdc")]),
+            excluded_intermediates: set([Token("DAI")]),
+            ..Default::default()
+        };
+        assert_eq!(
+            route(not_through_dai),
+            Some(vec![PoolId("eth-wbtc"), PoolId("wbtc-usdc")]),
+            "{:?}",
+            algo
+        );
+    }
+}
+
+#[test]
+fn test_circular_honours_filters() {
+    let graph = graph();
+    let cycle = |params: ExecutionParams| {
+        let params = ExecutionParams {
+            max_hops: 3,
+            ..params
+        };
+        circular(
+            Side::Sell,
+            &graph,
+            &Token("ETH"),
+            &Token("ETH"),
+            dec!(1),
+            params,
+        )
+        .map(|route| pools_used(&route))
+    };
+    // Buying DAI at a premium and selling it back through USDC is the best cycle
+    assert_eq!(
+        cycle(ExecutionParams::default()),
+        Some(vec![
+            PoolId("eth-dai"),
+            PoolId("dai-usdc"),
+            PoolId("eth-usdc")
+        ])
+    );
+
+    let without_dai = cycle(ExecutionParams {
+        excluded_intermediates: set([Token("DAI")]),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(
+        !without_dai.contains(&PoolId("eth-dai")),
+        "{:?}",
+        without_dai
+    );
+    let without_pool = cycle(ExecutionParams {
+        excluded_pools: set([PoolId("dai-usdc")]),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(
+        !without_pool.contains(&PoolId("dai-usdc")),
+        "{:?}",
+        without_pool
+    );
+    // The start token is an endpoint of the cycle, not an intermediate
+    assert!(
+        cycle(ExecutionParams {
+            allowed_intermediates: set([Token("USDC"), Token("DAI")]),
+            ..Default::default()
+        })
+        .is_some()
+    );
+    assert_eq!(
+        cycle(ExecutionParams {
+            excluded_tokens: set([Token("ETH")]),
+            ..Default::default()
+        }),
+        None
+    );
+}
@@ -0,0 +1,151 @@
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::adapter::graph_store::GraphStore;
+use crate::domain::types::*;
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+use std::sync::Arc;
+
+fn pool(id: &'static str, token0: &'static str, token1: &'static str) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0: dec!(1000),
+        reserve1: dec!(1000000),
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+#[test]
+fn test_update_reserves_keeps_adjacency_consistent() {
+    let mut graph = Graph::new(&[
+        pool("eth-usdc", "ETH", "USDC"),
+        pool("eth-dai", "ETH", "DAI"),
+    ]);
+    let id = PoolId("eth-usdc");
+
+    let previous = graph
+        .update_reserves(&id, dec!(2000), dec!(1000000))
+        .unwrap();
+    assert_eq!(previous.reserve0, dec!(1000));
+
+    for token in [Token("ETH"), Token("USDC")] {
+        let adjacent = graph.adjacency[&token].iter().find(|p| p.id == id).unwrap();
+        assert!(Arc::ptr_eq(adjacent, graph.pool(&id).unwrap()));
+        assert_eq!(adjacent.reserve0, dec!(2000));
+    }
+
+    let (_, route) = DefaultRouter
+        .compute_route(
+            Side::Buy,
+            &graph,
+            &Token("ETH"),
+            &Token("USDC"),
+            dec!(1),
+            ExecutionParams::default(),
+        )
+        .unwrap();
+    assert!(route.output_amount < dec!(500));
+
+    assert_eq!(
+        graph
+            .update_reserves(&PoolId("missing"), dec!(1), dec!(1))
+            .unwrap_err(),
+        GraphError::UnknownPool(PoolId("missing"))
+    );
+
+    // Empty pools and overflowing amounts have no output instead of panicking
+    graph
+        .update_reserves(&PoolId("eth-dai"), Decimal::ZERO, Decimal::ZERO)
+        .unwrap();
+    let empty = graph.pool(&PoolId("eth-dai")).unwrap();
+    assert_eq!(empty.get_output_amount(&Token("ETH"), Decimal::ZERO), None);
+    let pool = graph.pool(&id).unwrap();
+    assert_eq!(pool.get_output_amount(&Token("ETH"), Decimal::MAX), None);
+}
+
+#[test]
+fn test_insert_and_remove_pool() {
+    let mut graph = Graph::new(&[pool("eth-usdc", "ETH", "USDC")]);
+
+    graph.insert_pool(pool("usdc-dai", "USDC", "DAI")).unwrap();
+    assert!(graph.tokens().contains(&Token("DAI")));
+    assert_eq!(graph.adjacency[&Token("USDC")].len(), 2);
+    assert_eq!(
+        graph.insert_pool(pool("usdc-dai", "USDC", "DAI")),
+        Err(GraphError::DuplicatePool(PoolId("usdc-dai")))
+    );
+    assert_eq!(
+        Graph::try_new(&[
+            pool("eth-usdc", "ETH", "USDC"),
+            pool("eth-usdc", "ETH", "DAI")
+        ])
+        .unwrap_err(),
+        GraphError::DuplicatePool(PoolId("eth-usdc"))
+    );
+
+    let removed = graph.remove_pool(&PoolId("usdc-dai")).unwrap();
+    assert_eq!(removed.token1, Token("DAI"));
+    assert!(!graph.tokens().contains(&Token("DAI")));
+    assert_eq!(graph.adjacency[&Token("USDC")].len(), 1);
+    assert!(graph.pool(&PoolId("usdc-dai")).is_none());
+}
+
+#[test]
+fn test_graph_store_readers_never_see_half_applied_blocks() {
+    let store = GraphStore::new(
+        Graph::new(&[
+            pool("eth-usdc", "ETH", "USDC"),
+            pool("eth-dai", "ETH", "DAI"),
+        ])
+        .with_block_number(1),
+    );
+    let pinned = store.snapshot();
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..200 {
+                    let graph = store.snapshot();
+                    let usdc = graph.pool(&PoolId("eth-usdc")).unwrap();
+                    let dai = graph.pool(&PoolId("eth-dai")).unwrap();
+                    // Both pools are updated in the same block, so they must always agree
+                    assert_eq!(usdc.reserve0, dai.reserve0);
+                    assert_eq!(usdc.reserve0, Decimal::from(graph.block_number * 1000));
+                }
+            });
+        }
+
+        for block in 2..=100u64 {
+            let reserve = Decimal::from(block * 1000);
+            store
+                .update(block, |graph| {
+                    graph.update_reserves(&PoolId("eth-usdc"), reserve, dec!(1000000))?;
+                    graph.update_reserves(&PoolId("eth-dai"), reserve, dec!(1000000))
+                })
+                .unwrap();
+        }
+    });
+
+    assert_eq!(store.block_number(), 100);
+    assert_eq!(pinned.block_number, 1);
+    assert_eq!(
+        pinned.pool(&PoolId("eth-usdc")).unwrap().reserve0,
+        dec!(1000)
+    );
+
+    // A failed update publishes nothing
+    let failed = store.update(101, |graph| {
+        graph.update_reserves(&PoolId("eth-usdc"), dec!(1), dec!(1))?;
+        graph.update_reserves(&PoolId("missing"), dec!(1), dec!(1))
+    });
+    assert!(failed.is_err());
+    assert_eq!(store.block_number(), 100);
+    assert_eq!(
+        store.snapshot().pool(&PoolId("eth-usdc")).unwrap().reserve0,
+        dec!(100000)
+    );
+}
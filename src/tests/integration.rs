@@ -1,94 +1,15 @@
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rust_decimal::dec;
 
 use crate::adapter::graph::Graph;
+use crate::adapter::loader::load_pools;
 use crate::domain::{self, types::*};
 use crate::port::routing::{DefaultRouter, RoutingStrategy};
 use std::sync::Arc;
 
 fn get_reference_pools() -> Vec<Arc<Pool>> {
-    vec![
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("USDC"),
-            reserve0: dec!(2000),
-            reserve1: dec!(2000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("USDC"),
-            reserve0: dec!(1000),
-            reserve1: dec!(1000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("DAI"),
-            reserve0: dec!(1000),
-            reserve1: dec!(900000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("DAI"),
-            reserve0: dec!(3000),
-            reserve1: dec!(2800000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("DAI"),
-            reserve0: dec!(3000),
-            reserve1: dec!(3100000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("DAI"),
-            token1: Token("USDC"),
-            reserve0: dec!(1000000),
-            reserve1: dec!(1000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("DAI"),
-            token1: Token("USDC"),
-            reserve0: dec!(2000000),
-            reserve1: dec!(2000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("DAI"),
-            token1: Token("USDT"),
-            reserve0: dec!(1000000),
-            reserve1: dec!(900000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("DAI"),
-            token1: Token("USDT"),
-            reserve0: dec!(900000),
-            reserve1: dec!(1000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("USDT"),
-            reserve0: dec!(2000),
-            reserve1: dec!(2000000),
-            fee_bps: dec!(30),
-        },
-        Pool {
-            token0: Token("ETH"),
-            token1: Token("USDT"),
-            reserve0: dec!(10000),
-            reserve1: dec!(10000000),
-            fee_bps: dec!(30),
-        },
-    ]
-    .into_par_iter()
-    .map(Arc::new)
-    .collect()
+    load_pools(concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_pools.csv"))
+        .unwrap()
+        .pools
 }
 
 fn validate_route(route: (RoutingAlgo, Route), from: &'static str, to: &'static str) {
@@ -126,6 +47,7 @@ fn test_full_eth_usdc_routing_scenario() {
             },
             algo: RoutingAlgo::Auto,
             max_hops: 4,
+            ..Default::default()
         },
     );
     assert!(route1.is_some(), "ETH → USDC route not found");
@@ -143,6 +65,7 @@ fn test_full_eth_usdc_routing_scenario() {
             },
             algo: RoutingAlgo::Auto,
             max_hops: 4,
+            ..Default::default()
         },
     );
     assert!(route2.is_some(), "USDC → ETH route not found");
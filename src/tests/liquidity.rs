@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::graph::Graph;
+use crate::adapter::graph_store::GraphStore;
+use crate::adapter::liquidity::{PruneReason, apply_thresholds, spot_prices};
+use crate::application::quote::{QuoteRequest, quote};
+use crate::application::route_engine::execute;
+use crate::domain::types::*;
+use crate::port::routing::DefaultRouter;
+
+fn pool(
+    id: &'static str,
+    token0: &'static str,
+    token1: &'static str,
+    reserve0: Decimal,
+    reserve1: Decimal,
+) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0,
+        reserve1,
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+/// A deep ETH/USDC pool next to a manipulated dust pool quoting ETH at 10 000 USDC.
+fn graph() -> Graph {
+    Graph::new(&[
+        pool("eth-usdc", "ETH", "USDC", dec!(1000), dec!(2000000)),
+        pool("eth-usdc-dust", "ETH", "USDC", dec!(0.001), dec!(10)),
+        pool("eth-dai-thin", "ETH", "DAI", dec!(5), dec!(10000)),
+        pool("dai-usdc", "DAI", "USDC", dec!(1000000), dec!(1000000)),
+        pool("abc-xyz", "ABC", "XYZ", dec!(1000), dec!(1000)),
+    ])
+}
+
+fn thresholds() -> LiquidityThresholds {
+    LiquidityThresholds {
+        min_reserve: Some(dec!(1)),
+        min_tvl: Some(MinTvl {
+            value: dec!(100000),
+            numeraire: Token("USDC"),
+        }),
+    }
+}
+
+fn pools_used(
+    graph: &Graph,
+    to: &'static str,
+    amount: Decimal,
+    params: ExecutionParams,
+) -> Vec<PoolId> {
+    let (_, route) = execute(Side::Sell, graph, &Token("ETH"), &Token(to), amount, params).unwrap();
+    route
+        .steps
+        .iter()
+        .map(|step| step.pool.id.clone())
+        .collect()
+}
+
+#[test]
+fn test_graph_thresholds_prune_pools_and_report_why() {
+    // The deepest pool sets the price, not the dust pool
+    let prices = spot_prices(&graph(), &Token("USDC"));
+    assert_eq!(prices[&Token("ETH")], dec!(2000));
+    assert!(!prices.contains_key(&Token("ABC")));
+
+    assert_eq!(
+        pools_used(&graph(), "USDC", dec!(0.0001), ExecutionParams::default()),
+        [PoolId("eth-usdc-dust")]
+    );
+    let graph = graph().with_liquidity(thresholds());
+    let report = &graph.pruned;
+    assert_eq!((report.checked, report.pruned.len()), (5, 3));
+    assert_eq!(
+        report.pruned[&PoolId("eth-usdc-dust")],
+        PruneReason::LowReserve
+    );
+    assert_eq!(report.pruned[&PoolId("eth-dai-thin")], PruneReason::LowTvl);
+    assert_eq!(report.pruned[&PoolId("abc-xyz")], PruneReason::Unpriced);
+    assert_eq!(
+        report.to_string(),
+        "3 of 5 pools pruned (1 below min reserve, 1 below min TVL, 1 unpriced)"
+    );
+    assert_eq!(
+        pools_used(&graph, "USDC", dec!(0.0001), ExecutionParams::default()),
+        [PoolId("eth-usdc")]
+    );
+
+    // Pools are re-checked when their reserves change
+    let store = GraphStore::new(graph);
+    store
+        .update(1, |graph| {
+            graph.update_reserves(&PoolId("eth-usdc-dust"), dec!(100), dec!(200000))
+        })
+        .unwrap();
+    let pruned = &store.snapshot().pruned;
+    assert!(!pruned.is_pruned(&PoolId("eth-usdc-dust")));
+    assert_eq!(pruned.pruned.len(), 2);
+}
+
+#[test]
+fn test_request_thresholds_apply_on_top_of_the_graph() {
+    let graph = graph();
+    let params = ExecutionParams {
+        liquidity: thresholds(),
+        ..Default::default()
+    };
+    for algo in [RoutingAlgo::Dijkstra, RoutingAlgo::Bfs, RoutingAlgo::Auto] {
+        let params = ExecutionParams {
+            algo,
+            ..params.clone()
+        };
+        assert_eq!(
+            pools_used(&graph, "USDC", dec!(0.0001), params.clone()),
+            [PoolId("eth-usdc")],
+            "{:?}",
+            algo
+        );
+        // The thin pool is skipped for the deeper detour
+        assert_eq!(
+            pools_used(&graph, "DAI", dec!(1), params),
+            [PoolId("eth-usdc"), PoolId("dai-usdc")],
+            "{:?}",
+            algo
+        );
+    }
+    // The request did not change the graph
+    assert!(graph.pruned.pruned.is_empty());
+}
+
+#[test]
+fn test_quote_reports_the_pools_its_thresholds_pruned() {
+    let graph = graph();
+    let params = ExecutionParams {
+        liquidity: thresholds(),
+        ..Default::default()
+    };
+
+    // Applied thresholds move to excluded pools, so applying them again is a no-op
+    let (applied, report) = apply_thresholds(&graph, params.clone());
+    assert_eq!((report.checked, report.pruned.len()), (5, 3));
+    assert!(applied.liquidity.is_empty());
+    assert_eq!(applied.excluded_pools.as_ref().unwrap().len(), 3);
+    let (_, again) = apply_thresholds(&graph, applied);
+    assert_eq!(again.checked, 0);
+
+    let request = QuoteRequest {
+        chain_id: MAINNET,
+        side: Side::Sell,
+        from: Token("ETH"),
+        to: Token("USDC"),
+        amount_in: dec!(0.0001),
+        params,
+    };
+    let found = quote(&DefaultRouter, &graph, &request).unwrap();
+    assert_eq!(found.pruned, report);
+    assert_eq!(found.route.steps[0].pool.id, PoolId("eth-usdc"));
+}
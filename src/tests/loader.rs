@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+
+use crate::adapter::graph::GraphError;
+use crate::adapter::loader::{LoadError, parse_csv, parse_json};
+use crate::domain::types::*;
+
+const CSV_HEADER: &str =
+    "id,token0_symbol,token0_address,token1_symbol,token1_address,reserve0,reserve1,fee_bps\n";
+
+fn row_line(err: LoadError) -> usize {
+    match err {
+        LoadError::Row { line, .. } => line,
+        other => panic!("expected a row error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_json_array_and_lines() {
+    let array = r#"[
+        {
+            "id": "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc",
+            "token0": {"symbol": "USDC", "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"},
+            "token1": {"symbol": "WETH", "address": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"},
+            "reserve0": "30000000.123456",
+            "reserve1": "10000",
+            "fee_bps": 30
+        },
+        {
+            "id": "dai-weth",
+            "token0": {"symbol": "DAI"},
+            "token1": {"symbol": "WETH"},
+            "reserve0": "20000000",
+            "reserve1": "6600",
+            "fee_bps": 30
+        }
+    ]"#;
+    let dump = parse_json(array).unwrap();
+    assert_eq!(dump.pools.len(), 2);
+    assert_eq!(dump.pools[0].reserve0, dec!(30000000.123456));
+    assert_eq!(dump.pools[1].token0, Token("DAI"));
+    assert_eq!(dump.token_addresses.len(), 2);
+    assert_eq!(
+        dump.graph()
+            .unwrap()
+            .pool(&PoolId("dai-weth"))
+            .map(|pool| pool.fee_bps),
+        Some(dec!(30))
+    );
+
+    let lines = r#"{"id": "a", "token0": {"symbol": "X"}, "token1": {"symbol": "Y"}, "reserve0": "1", "reserve1": "2", "fee_bps": 30}
+
+{"id": "b", "token0": {"symbol": "X"}, "token1": {"symbol": "Z"}, "reserve0": "1", "reserve1": "oops", "fee_bps": 30}"#;
+    let err = parse_json(lines).unwrap_err();
+    assert!(err.to_string().contains("invalid decimal amount \"oops\""));
+    assert_eq!(row_line(err), 3);
+
+    let bad_entry = array.replace("\"6600\"", "\"-1\"");
+    assert_eq!(row_line(parse_json(&bad_entry).unwrap_err()), 10);
+
+    // Pools added to a dump after parsing are checked again instead of panicking
+    let mut dump = parse_json(array).unwrap();
+    dump.pools.push(Arc::clone(&dump.pools[1]));
+    assert_eq!(
+        dump.graph().unwrap_err(),
+        GraphError::DuplicatePool(PoolId("dai-weth"))
+    );
+}
+
+#[test]
+fn test_parse_csv_reports_malformed_rows() {
+    let valid = format!(
+        "{CSV_HEADER}eth-usdc,ETH,0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2,USDC,,2000,2000000,30\n"
+    );
+    let dump = parse_csv(&valid).unwrap();
+    assert_eq!(dump.pools[0].reserve1, dec!(2000000));
+    assert_eq!(dump.token_addresses.len(), 1);
+
+    let cases = [
+        ("a,ETH,,USDC,,2000,abc,30\n", "invalid decimal"),
+        ("a,ETH,0x1234,USDC,,2000,2000000,30\n", "invalid address"),
+        ("a,ETH,,ETH,,2000,2000000,30\n", "with itself"),
+        ("a,ETH,,USDC,,2000,2000000\n", "found record with 7 fields"),
+        ("eth-usdc,DAI,,USDC,,1,1,30\n", "duplicate pool id"),
+    ];
+    for (row, message) in cases {
+        let input = format!("{valid}{row}");
+        let err = parse_csv(&input).unwrap_err();
+        assert!(err.to_string().contains(message), "{err}");
+        assert_eq!(row_line(err), 3, "{row}");
+    }
+}
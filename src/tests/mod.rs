@@ -1,2 +1,26 @@
+pub mod chains;
+#[cfg(feature = "serde")]
+pub mod cli;
 pub mod common;
+pub mod events;
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod filters;
+pub mod graph;
 pub mod integration;
+pub mod liquidity;
+pub mod loader;
+pub mod parallel_pools;
+pub mod router_v2;
+#[cfg(feature = "server")]
+pub mod rpc;
+pub mod rpc_provider;
+#[cfg(feature = "serde")]
+pub mod serialization;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod snapshot;
+pub mod token_list;
+pub mod universal_router;
+pub mod venues;
+pub mod verify;
@@ -0,0 +1,93 @@
+use rust_decimal::dec;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::loader::parse_csv;
+use crate::adapter::parallel_pools::{ParallelPoolPolicy, TradeSizes, prune_parallel_pools};
+use crate::application::route_engine::execute;
+use crate::domain::types::*;
+
+fn demo_graph() -> Graph {
+    parse_csv(include_str!("../../data/demo_pools.csv"))
+        .unwrap()
+        .graph()
+        .unwrap()
+        .with_block_number(7)
+}
+
+#[test]
+fn test_keep_deepest_pools_per_pair() {
+    let graph = demo_graph();
+    let (pruned, removed) = prune_parallel_pools(&graph, &ParallelPoolPolicy::TopN(1));
+    let mut kept: Vec<&str> = pruned.pools.keys().map(|id| id.0).collect();
+    kept.sort();
+    // Equally deep DAI/USDT pools are told apart by ID
+    assert_eq!(
+        kept,
+        [
+            "dai-usdc-2",
+            "dai-usdt-1",
+            "eth-dai-3",
+            "eth-usdc-1",
+            "eth-usdt-2"
+        ]
+    );
+    assert_eq!(removed.len(), 6);
+    assert_eq!(pruned.block_number, 7);
+    assert_eq!(pruned.tokens().len(), graph.tokens().len());
+
+    let (all, removed) = prune_parallel_pools(&graph, &ParallelPoolPolicy::TopN(3));
+    assert!(removed.is_empty());
+    assert_eq!(all.pools.len(), 11);
+}
+
+#[test]
+fn test_dominated_pools_are_never_best() {
+    let graph = demo_graph();
+    let (pruned, removed) =
+        prune_parallel_pools(&graph, &ParallelPoolPolicy::Undominated(TradeSizes::Any));
+    // Same price as a deeper pool of their pair
+    assert_eq!(
+        removed,
+        [
+            PoolId("dai-usdc-1"),
+            PoolId("eth-usdc-2"),
+            PoolId("eth-usdt-1")
+        ]
+    );
+
+    let params = ExecutionParams {
+        algo: RoutingAlgo::BestDirect,
+        ..Default::default()
+    };
+    for pool in graph.pools.values() {
+        for (from, to) in [(&pool.token0, &pool.token1), (&pool.token1, &pool.token0)] {
+            for amount in [
+                dec!(0.001),
+                dec!(1),
+                dec!(100),
+                dec!(100000),
+                dec!(10000000),
+            ] {
+                let best = |graph: &Graph| {
+                    execute(Side::Sell, graph, from, to, amount, params.clone())
+                        .unwrap()
+                        .1
+                        .output_amount
+                };
+                assert_eq!(best(&pruned), best(&graph), "{} → {} {}", from, to, amount);
+            }
+        }
+    }
+
+    // eth-dai-2 is only ever best for DAI → ETH trades above 1 000 DAI
+    let (_, removed) = prune_parallel_pools(
+        &graph,
+        &ParallelPoolPolicy::Undominated(TradeSizes::Between {
+            min: dec!(100),
+            max: dec!(1000),
+            numeraire: Token("USDC"),
+        }),
+    );
+    assert!(removed.contains(&PoolId("eth-dai-2")), "{:?}", removed);
+    assert!(!removed.contains(&PoolId("eth-dai-1")), "{:?}", removed);
+}
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::router_v2::{CalldataError, RouterCall, SwapOptions, router_call};
+use crate::domain::types::*;
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+
+pub const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+pub const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+pub const DAI: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+pub const RECIPIENT: Address = Address([0x11; 20]);
+pub const DEADLINE: u64 = 1_700_000_000;
+
+pub fn address(hex: &str) -> Address {
+    hex.parse().unwrap()
+}
+
+/// Expected calldata was produced independently with `ethabi`.
+#[test]
+fn test_router_calls_match_reference_encodings() {
+    let cases = [
+        (
+            RouterCall::SwapExactTokensForTokens {
+                amount_in: 1_000_000_000,
+                amount_out_min: 995_000_000_000_000_000_000,
+                path: vec![address(USDC), address(WETH), address(DAI)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "38ed1739",
+                "000000000000000000000000000000000000000000000000000000003b9aca00",
+                "000000000000000000000000000000000000000000000035f0661c4399ac0000",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "0000000000000000000000006b175474e89094c44da98b954eedeac495271d0f",
+            ),
+        ),
+        (
+            RouterCall::SwapTokensForExactTokens {
+                amount_out: 1_000_000_000_000_000_000,
+                amount_in_max: 2_010_000_000,
+                path: vec![address(USDC), address(WETH)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "8803dbee",
+                "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+                "0000000000000000000000000000000000000000000000000000000077ce2a80",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            ),
+        ),
+        (
+            RouterCall::SwapExactETHForTokens {
+                amount_in: 1_000_000_000_000_000_000,
+                amount_out_min: 1_990_000_000,
+                path: vec![address(WETH), address(USDC)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "7ff36ab5",
+                "00000000000000000000000000000000000000000000000000000000769cfd80",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            ),
+        ),
+        (
+            RouterCall::SwapTokensForExactETH {
+                amount_out: 500_000_000_000_000_000,
+                amount_in_max: 1_005_000_000,
+                path: vec![address(USDC), address(WETH)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "4a25d94a",
+                "00000000000000000000000000000000000000000000000006f05b59d3b20000",
+                "000000000000000000000000000000000000000000000000000000003be71540",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            ),
+        ),
+        (
+            RouterCall::SwapExactTokensForETH {
+                amount_in: 1_000_000_000,
+                amount_out_min: 497_500_000_000_000_000,
+                path: vec![address(USDC), address(WETH)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "18cbafe5",
+                "000000000000000000000000000000000000000000000000000000003b9aca00",
+                "00000000000000000000000000000000000000000000000006e7799d37c1c000",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            ),
+        ),
+        (
+            RouterCall::SwapETHForExactTokens {
+                amount_out: 2_000_000_000,
+                amount_in_max: 1_010_000_000_000_000_000,
+                path: vec![address(WETH), address(USDC)],
+                to: RECIPIENT,
+                deadline: DEADLINE,
+            },
+            concat!(
+                "fb3bdb41",
+                "0000000000000000000000000000000000000000000000000000000077359400",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            ),
+        ),
+    ];
+    for (call, expected) in cases {
+        assert_eq!(hex::encode(call.encode()), expected, "{:?}", call);
+    }
+}
+
+pub fn info(symbol: &'static str, address: Address, decimals: u32) -> TokenInfo {
+    TokenInfo {
+        chain_id: 1,
+        address,
+        symbol: Token(symbol),
+        decimals,
+        tags: Vec::new(),
+    }
+}
+
+#[test]
+fn test_router_call_from_route() {
+    let pool = |id, token0, token1, reserve0, reserve1| {
+        Arc::new(Pool {
+            id: PoolId(id),
+            token0: Token(token0),
+            token1: Token(token1),
+            reserve0,
+            reserve1,
+            fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
+        })
+    };
+    let graph = Graph::new(&[
+        pool("usdc-weth", "USDC", "WETH", dec!(2000000), dec!(1000)),
+        pool("weth-dai", "WETH", "DAI", dec!(1000), dec!(2000000)),
+    ])
+    .with_token_info([
+        info("USDC", address(USDC), 6),
+        info("WETH", address(WETH), 18),
+        info("DAI", address(DAI), 18),
+    ])
+    .unwrap();
+    let mut options = SwapOptions {
+        recipient: RECIPIENT,
+        deadline: DEADLINE,
+        slippage: Slippage {
+            tolerance_bps: dec!(50),
+        },
+        native: None,
+    };
+    let route = |from, to, amount| {
+        DefaultRouter
+            .compute_route(
+                Side::Sell,
+                &graph,
+                &Token(from),
+                &Token(to),
+                amount,
+                ExecutionParams::default(),
+            )
+            .unwrap()
+            .1
+    };
+
+    let usdc_to_dai = route("USDC", "DAI", dec!(1000));
+    let call = router_call(&graph, Side::Sell, &usdc_to_dai, dec!(1000), &options).unwrap();
+    let min_out = (usdc_to_dai.output_amount * dec!(0.995) * dec!(1e18)).trunc();
+    assert_eq!(
+        call,
+        RouterCall::SwapExactTokensForTokens {
+            amount_in: 1_000_000_000,
+            amount_out_min: min_out.to_u128().unwrap(),
+            path: vec![address(USDC), address(WETH), address(DAI)],
+            to: RECIPIENT,
+            deadline: DEADLINE,
+        }
+    );
+    assert_eq!(call.value(), 0);
+
+    options.native = Some(Token("WETH"));
+    let weth_to_usdc = route("WETH", "USDC", dec!(0.5));
+    let call = router_call(&graph, Side::Sell, &weth_to_usdc, dec!(0.5), &options).unwrap();
+    assert!(matches!(call, RouterCall::SwapExactETHForTokens { .. }));
+    assert_eq!(call.value(), 500_000_000_000_000_000);
+
+    let call = router_call(&graph, Side::Buy, &usdc_to_dai, dec!(1000), &options).unwrap();
+    let RouterCall::SwapTokensForExactTokens { amount_in_max, .. } = call else {
+        panic!("expected an exact output swap, got {:?}", call);
+    };
+    assert_eq!(amount_in_max, 1_005_000_000);
+
+    let unknown = Graph::new(&[pool("usdc-weth", "USDC", "WETH", dec!(2000000), dec!(1000))]);
+    assert_eq!(
+        router_call(&unknown, Side::Sell, &weth_to_usdc, dec!(0.5), &options),
+        Err(CalldataError::UnknownToken(Token("WETH")))
+    );
+}
@@ -0,0 +1,79 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+
+use crate::adapter::graph::Graph;
+use crate::adapter::graph_store::GraphStore;
+use crate::port::routing::DefaultRouter;
+use crate::server::rpc::{INVALID_REQUEST, METHOD_NOT_FOUND, NO_ROUTE, PARSE_ERROR, serve};
+use crate::server::service::QuoteService;
+
+fn run(lines: &[Value]) -> Vec<Value> {
+    let service = QuoteService::new(DefaultRouter, Arc::new(GraphStore::new(Graph::new(&[]))));
+    let input: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    let mut output = Vec::new();
+    serve(&service, Cursor::new(input), &mut output).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_rpc_loads_updates_and_quotes() {
+    let pool = |id: &str, reserve0: &str, reserve1: &str| {
+        json!({"id": id, "token0": "ETH", "token1": "USDC", "reserve0": reserve0,
+               "reserve1": reserve1, "fee_bps": "30"})
+    };
+    let quote = json!({"from": "ETH", "to": "USDC", "amount_in": "1"});
+    let responses = run(&[
+        json!({"jsonrpc": "2.0", "id": 1, "method": "load_pools",
+               "params": {"pools": [pool("a", "100", "200000")], "block_number": 7}}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "quote", "params": quote}),
+        // A notification is applied but not answered
+        json!({"jsonrpc": "2.0", "method": "update_reserves",
+               "params": {"block_number": 8, "updates": [{"id": "a", "reserve0": "100", "reserve1": "400000"}]}}),
+        json!({"jsonrpc": "2.0", "id": 3, "method": "quote", "params": quote}),
+        json!({"jsonrpc": "2.0", "id": 4, "method": "health"}),
+    ]);
+
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["result"]["chains"][0]["pools"], 1);
+    assert_eq!(responses[0]["result"]["chains"][0]["block_number"], 7);
+    assert_eq!(responses[1]["id"], 2);
+    let before: f64 = responses[1]["result"]["output_amount"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let after: f64 = responses[2]["result"]["output_amount"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(after > before * 1.9);
+    assert_eq!(responses[3]["result"]["chains"][0]["block_number"], 8);
+}
+
+#[test]
+fn test_rpc_reports_errors() {
+    let responses = run(&[
+        json!("not a request"),
+        json!({"jsonrpc": "2.0", "id": "a", "method": "swap"}),
+        json!({"jsonrpc": "2.0", "id": "b", "method": "quote",
+               "params": {"from": "ETH", "to": "DAI", "amount_in": "1"}}),
+    ]);
+    assert_eq!(responses[0]["error"]["code"], INVALID_REQUEST);
+    assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+    assert_eq!(responses[2]["id"], "b");
+    assert_eq!(responses[2]["error"]["code"], NO_ROUTE);
+
+    let service = QuoteService::new(DefaultRouter, Arc::new(GraphStore::new(Graph::new(&[]))));
+    let mut output = Vec::new();
+    serve(&service, Cursor::new("{oops\n"), &mut output).unwrap();
+    let response: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+    assert_eq!(response["id"], Value::Null);
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use rust_decimal::dec;
+use serde_json::{Value, json};
+
+use crate::adapter::rpc_provider::{HttpTransport, PoolStateProvider, ProviderError};
+use crate::domain::types::*;
+use crate::tests::router_v2::{DAI, USDC, WETH, address, info};
+
+const USDC_WETH: &str = "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc";
+const WETH_DAI: &str = "0xa478c2975ab1ea89e8196811f51a7b7ade33eb11";
+const UNISWAP_FACTORY: &str = "0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f";
+
+/// Pair address → (token0, token1, reserve0, reserve1), raw units.
+type Pairs = Arc<Mutex<HashMap<Address, (Address, Address, u128, u128)>>>;
+
+fn word(value: u128) -> String {
+    format!("{:064x}", value)
+}
+
+/// Answers `eth_chainId`, `eth_blockNumber` and batched `eth_call`s on the pairs at
+/// block 0x112a880, returning batches in reverse order like some nodes do.
+fn mock_node(pairs: Pairs) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+
+            let pairs = pairs.lock().unwrap();
+            let answer = |call: &Value| {
+                let result = match call["method"].as_str().unwrap() {
+                    "eth_blockNumber" => "0x112a880".to_string(),
+                    "eth_chainId" => "0x1".to_string(),
+                    "eth_getCode" if call["params"][1] == "latest" => {
+                        "0x600160005260206000f3".to_string()
+                    }
+                    _ if call["params"][1] != "0x112a880" => {
+                        return json!({"jsonrpc": "2.0", "id": call["id"], "error": {"code": -32000, "message": "unexpected block tag"}});
+                    }
+                    _ => {
+                        let to: Address =
+                            call["params"][0]["to"].as_str().unwrap().parse().unwrap();
+                        let Some(&(token0, token1, reserve0, reserve1)) = pairs.get(&to) else {
+                            return json!({"jsonrpc": "2.0", "id": call["id"], "error": {"code": -32000, "message": "execution reverted"}});
+                        };
+                        let token = |token: Address| format!("{:0>64}", hex::encode(token.0));
+                        match call["params"][0]["data"].as_str().unwrap() {
+                            "0x0902f1ac" => format!(
+                                "0x{}{}{}",
+                                word(reserve0),
+                                word(reserve1),
+                                word(1_700_000_000)
+                            ),
+                            "0xc45a0155" => format!("0x{}", token(address(UNISWAP_FACTORY))),
+                            "0x0dfe1681" => format!("0x{}", token(token0)),
+                            "0xd21220a7" => format!("0x{}", token(token1)),
+                            data => panic!("unexpected call {}", data),
+                        }
+                    }
+                };
+                json!({"jsonrpc": "2.0", "id": call["id"], "result": result})
+            };
+            let response = match &request {
+                Value::Array(calls) => Value::Array(calls.iter().rev().map(answer).collect()),
+                call => answer(call),
+            }
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+#[test]
+fn test_load_and_refresh_graph_from_rpc() {
+    let pairs: Pairs = Arc::new(Mutex::new(HashMap::from([
+        (
+            address(USDC_WETH),
+            (
+                address(USDC),
+                address(WETH),
+                30_000_000_000_000,
+                10_000 * 10u128.pow(18),
+            ),
+        ),
+        (
+            address(WETH_DAI),
+            (
+                address(DAI),
+                address(WETH),
+                6_000_000 * 10u128.pow(18),
+                2_000 * 10u128.pow(18),
+            ),
+        ),
+    ])));
+    let url = mock_node(Arc::clone(&pairs));
+    // Batches of two calls split the eight pair calls over four requests
+    let provider = PoolStateProvider::new(HttpTransport::new(&url).unwrap()).with_batch_size(2);
+    let tokens = [
+        info("USDC", address(USDC), 6),
+        info("WETH", address(WETH), 18),
+    ];
+
+    let mut graph = provider
+        .load_graph(&[address(USDC_WETH), address(WETH_DAI)], &tokens)
+        .unwrap();
+    assert_eq!(graph.block_number, 18_000_000);
+    let pool = graph
+        .pool(&PoolId::from_address(&address(USDC_WETH)))
+        .unwrap();
+    assert_eq!(
+        (pool.token0.clone(), pool.token1.clone()),
+        (Token("USDC"), Token("WETH"))
+    );
+    assert_eq!(
+        (pool.venue.clone(), pool.fee_bps),
+        (Venue::UNISWAP, dec!(30))
+    );
+    assert_eq!(
+        (pool.reserve0, pool.reserve1),
+        (dec!(30000000), dec!(10000))
+    );
+    assert_eq!(pool.fee_bps, dec!(30));
+    // DAI is not in the token list: named by address, 18 decimals assumed
+    let pool = graph
+        .pool(&PoolId::from_address(&address(WETH_DAI)))
+        .unwrap();
+    assert_eq!(pool.token0, Token(DAI));
+    assert_eq!(pool.reserve0, dec!(6000000));
+
+    pairs
+        .lock()
+        .unwrap()
+        .get_mut(&address(USDC_WETH))
+        .unwrap()
+        .2 = 31_000_000_000_000;
+    assert_eq!(provider.refresh(&mut graph).unwrap(), 2);
+    let pool = graph
+        .pool(&PoolId::from_address(&address(USDC_WETH)))
+        .unwrap();
+    assert_eq!(pool.reserve0, dec!(31000000));
+
+    assert_eq!(
+        provider.code(address(USDC_WETH)).unwrap(),
+        hex::decode("600160005260206000f3").unwrap()
+    );
+
+    let missing = address("0x0000000000000000000000000000000000000bad");
+    let err = provider.load_graph(&[missing], &tokens).unwrap_err();
+    assert!(
+        matches!(err, ProviderError::Call { pair, .. } if pair == missing),
+        "{}",
+        err
+    );
+}
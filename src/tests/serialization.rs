@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+use serde_json::json;
+
+use crate::adapter::graph::Graph;
+use crate::application::route_engine::execute;
+use crate::domain::serialization::RouteRecord;
+use crate::domain::types::*;
+
+fn pools() -> Vec<Arc<Pool>> {
+    vec![
+        Arc::new(Pool {
+            id: PoolId("eth-usdc"),
+            token0: Token("ETH"),
+            token1: Token("USDC"),
+            reserve0: dec!(1000),
+            reserve1: dec!(2000000.000001),
+            fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
+        }),
+        Arc::new(Pool {
+            id: PoolId("usdc-dai"),
+            token0: Token("USDC"),
+            token1: Token("DAI"),
+            reserve0: dec!(5000000),
+            reserve1: dec!(5000000),
+            fee_bps: dec!(5),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
+        }),
+    ]
+}
+
+#[test]
+fn test_domain_types_round_trip_with_string_decimals() {
+    let pool = pools().remove(0);
+    let value = serde_json::to_value(&*pool).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "id": "eth-usdc",
+            "token0": "ETH",
+            "token1": "USDC",
+            "reserve0": "1000",
+            "reserve1": "2000000.000001",
+            "fee_bps": "30",
+            "venue": "uniswap",
+            "chain_id": 1,
+        })
+    );
+    assert_eq!(serde_json::from_value::<Pool>(value).unwrap(), *pool);
+
+    let params: ExecutionParams =
+        serde_json::from_value(json!({"algo": "a_star", "slippage": {"tolerance_bps": "50"}}))
+            .unwrap();
+    assert_eq!(params.algo, RoutingAlgo::AStar);
+    assert_eq!(params.slippage.tolerance_bps, dec!(50));
+    assert_eq!(params.max_hops, ExecutionParams::default().max_hops);
+
+    let address: Address =
+        serde_json::from_value(json!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")).unwrap();
+    assert_eq!(
+        serde_json::to_value(address).unwrap(),
+        json!("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    );
+    assert!(serde_json::from_value::<Address>(json!("0x1234")).is_err());
+}
+
+#[test]
+fn test_route_references_pools_by_id() {
+    let graph = Graph::new(&pools());
+    let params = ExecutionParams {
+        algo: RoutingAlgo::Bfs,
+        ..Default::default()
+    };
+    let (_, route) = execute(
+        Side::Sell,
+        &graph,
+        &Token("ETH"),
+        &Token("DAI"),
+        dec!(1),
+        params,
+    )
+    .unwrap();
+
+    let value = serde_json::to_value(&route).unwrap();
+    assert_eq!(
+        value["steps"],
+        json!([
+            {"from": "ETH", "to": "USDC", "pool": "eth-usdc", "venue": "uniswap"},
+            {"from": "USDC", "to": "DAI", "pool": "usdc-dai", "venue": "uniswap"},
+        ])
+    );
+    assert_eq!(
+        value["output_amount"],
+        json!(route.output_amount.to_string())
+    );
+
+    let record: RouteRecord = serde_json::from_value(value).unwrap();
+    let resolved = record.resolve(|id| graph.pool(id).cloned()).unwrap();
+    assert_eq!(resolved.output_amount, route.output_amount);
+    assert!(Arc::ptr_eq(&resolved.steps[1].pool, &route.steps[1].pool));
+
+    let empty = Graph::new(&[]);
+    assert!(record.resolve(|id| empty.pool(id).cloned()).is_none());
+}
@@ -0,0 +1,324 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+
+use crate::adapter::graph_store::GraphStore;
+use crate::adapter::loader::load_graph;
+use crate::domain::types::interned;
+use crate::infra::route_cache::CachedRouter;
+use crate::port::routing::DefaultRouter;
+use crate::server::service::QuoteService;
+use crate::server::{QuoteServer, ServerHandle};
+
+fn demo_store() -> Arc<GraphStore> {
+    let graph = load_graph(concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_pools.csv")).unwrap();
+    Arc::new(GraphStore::new(graph.with_block_number(100)))
+}
+
+fn start() -> ServerHandle {
+    QuoteServer::new(QuoteService::new(DefaultRouter, demo_store()))
+        .spawn("127.0.0.1:0")
+        .unwrap()
+}
+
+fn start_cached() -> ServerHandle {
+    QuoteServer::new(QuoteService::new(
+        CachedRouter::new(DefaultRouter),
+        demo_store(),
+    ))
+    .spawn("127.0.0.1:0")
+    .unwrap()
+}
+
+fn call(addr: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_server_quotes_over_http() {
+    let server = start();
+    let addr = server.local_addr();
+
+    let (status, health) = call(addr, "GET", "/health", None);
+    assert_eq!(status, 200);
+    assert_eq!(
+        health,
+        json!({"status": "ok", "chains": [
+            {"chain_id": 1, "block_number": 100, "tokens": 4, "pools": 11},
+        ]})
+    );
+
+    let request =
+        json!({"from": "ETH", "to": "USDC", "amount_in": "10", "params": {"algo": "bfs"}});
+    let (status, quote) = call(addr, "POST", "/quote", Some(request.clone()));
+    assert_eq!(status, 200);
+    assert_eq!(quote["algo"], "bfs");
+    assert_eq!(quote["route"]["steps"][0]["from"], "ETH");
+    assert_eq!(quote["pruned"], json!({"checked": 0, "pruned": {}}));
+
+    let thresholds = json!({
+        "from": "ETH", "to": "USDC", "amount_in": "10",
+        "params": {"liquidity": {"min_reserve": "2000"}},
+    });
+    let (status, thresholded) = call(addr, "POST", "/quote", Some(thresholds));
+    assert_eq!(status, 200);
+    assert_eq!(
+        thresholded["pruned"],
+        json!({"checked": 11, "pruned": {"eth-usdc-2": "low_reserve", "eth-dai-1": "low_reserve"}})
+    );
+
+    let missing = json!({
+        "from": "ETH", "to": "USDC", "amount_in": "10", "params": {"allowed_pools": []},
+    });
+    let (status, error) = call(addr, "POST", "/quote", Some(missing.clone()));
+    assert_eq!(status, 422);
+    assert!(error["error"].as_str().unwrap().contains("no route"));
+
+    // Names no graph knows are rejected rather than interned
+    let unknown = json!({"from": "ETH", "to": "NOT-A-LOADED-TOKEN", "amount_in": "10"});
+    let (status, error) = call(addr, "POST", "/quote", Some(unknown));
+    assert_eq!(status, 400);
+    assert!(
+        error["error"]
+            .as_str()
+            .unwrap()
+            .contains("unknown token `NOT-A-LOADED-TOKEN`")
+    );
+    assert_eq!(interned("NOT-A-LOADED-TOKEN"), None);
+
+    let (status, batch) = call(
+        addr,
+        "POST",
+        "/quote/batch",
+        Some(json!([request, missing])),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(batch[0]["output_amount"], quote["output_amount"]);
+    assert!(batch[1]["error"].is_string());
+
+    let (status, found) = call(addr, "GET", "/arbitrage", None);
+    assert_eq!(status, 200);
+    assert!(
+        found
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|item| item["arbitrage"]["arbitrage_type"] == "circular")
+    );
+
+    assert_eq!(
+        call(addr, "POST", "/quote", Some(json!({"from": "ETH"}))).0,
+        400
+    );
+    for amount in ["0", "-1", "79228162514264337593543950335"] {
+        let request = json!({"from": "ETH", "to": "USDC", "amount_in": amount});
+        let (status, error) = call(addr, "POST", "/quote", Some(request));
+        assert_eq!(status, 400, "{}", amount);
+        assert!(error["error"].as_str().unwrap().contains(amount));
+    }
+    assert_eq!(call(addr, "GET", "/quote", None).0, 405);
+    assert_eq!(call(addr, "GET", "/nope", None).0, 404);
+    server.shutdown();
+}
+
+#[test]
+fn test_server_applies_pool_updates() {
+    let server = start();
+    let addr = server.local_addr();
+    let request = json!({
+        "from": "ETH", "to": "USDC", "amount_in": "1", "params": {"algo": "best_direct"},
+    });
+    let (_, before) = call(addr, "POST", "/quote", Some(request.clone()));
+    let pool = before["route"]["steps"][0]["pool"].clone();
+
+    let update = json!({
+        "block_number": 101,
+        "updates": [{"id": pool, "reserve0": "1", "reserve1": "1"}],
+    });
+    let (status, updated) = call(addr, "POST", "/pools", Some(update));
+    assert_eq!(status, 200);
+    assert_eq!(updated, json!({"block_number": 101, "updated": 1}));
+
+    let (_, after) = call(addr, "POST", "/quote", Some(request));
+    assert_ne!(after["output_amount"], before["output_amount"]);
+    assert_eq!(
+        call(addr, "GET", "/health", None).1["chains"][0]["block_number"],
+        101
+    );
+
+    let unknown = json!({"updates": [
+        {"id": pool, "reserve0": "5", "reserve1": "5"},
+        {"id": "not-a-loaded-pool", "reserve0": "1", "reserve1": "1"},
+    ]});
+    assert_eq!(call(addr, "POST", "/pools", Some(unknown)).0, 400);
+    let (_, unchanged) = call(
+        addr,
+        "POST",
+        "/quote",
+        Some(json!({
+            "from": "ETH", "to": "USDC", "amount_in": "1", "params": {"algo": "best_direct"},
+        })),
+    );
+    assert_eq!(unchanged["output_amount"], after["output_amount"]);
+}
+
+#[test]
+fn test_cached_server_quotes_the_latest_pools() {
+    let server = start_cached();
+    let addr = server.local_addr();
+    let quote = |algo: &str| {
+        let request = json!({
+            "from": "ETH", "to": "USDC", "amount_in": "1", "params": {"algo": algo},
+        });
+        call(addr, "POST", "/quote", Some(request)).1
+    };
+
+    let before = quote("auto");
+    assert_ne!(before["route"]["steps"][0]["pool"], "eth-usdc-2");
+    // Each algorithm is quoted by itself, not from another one's cached path
+    assert_eq!(quote("dijkstra")["algo"], "dijkstra");
+    assert_eq!(quote("bfs")["algo"], "bfs");
+
+    // A pool off the cached path becomes the best one
+    let update = json!({
+        "updates": [{"id": "eth-usdc-2", "reserve0": "1000", "reserve1": "9000000"}],
+    });
+    assert_eq!(call(addr, "POST", "/pools", Some(update)).0, 200);
+    let after = quote("auto");
+    assert_eq!(after["route"]["steps"][0]["pool"], "eth-usdc-2");
+    assert_ne!(after["output_amount"], before["output_amount"]);
+    server.shutdown();
+}
+
+fn ws_connect(addr: SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "GET /subscribe HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+    )
+    .unwrap();
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8(head).unwrap();
+    assert!(head.starts_with("HTTP/1.1 101"));
+    assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+    stream
+}
+
+fn ws_send(stream: &mut TcpStream, message: Value) {
+    let payload = message.to_string().into_bytes();
+    let mask = [1u8, 2, 3, 4];
+    let mut frame = vec![0x81, 0x80 | 126];
+    frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame).unwrap();
+}
+
+fn ws_receive(stream: &mut TcpStream) -> Value {
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head).unwrap();
+    assert_eq!(head[0], 0x81);
+    let len = match head[1] {
+        126 => {
+            let mut len = [0u8; 2];
+            stream.read_exact(&mut len).unwrap();
+            u16::from_be_bytes(len) as usize
+        }
+        len => len as usize,
+    };
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).unwrap();
+    serde_json::from_slice(&payload).unwrap()
+}
+
+#[test]
+fn test_server_pushes_changed_quotes_to_subscribers() {
+    let server = start();
+    let addr = server.local_addr();
+    let mut stream = ws_connect(addr);
+
+    ws_send(
+        &mut stream,
+        json!({"type": "subscribe", "id": "eth-usdc", "from": "ETH", "to": "USDC",
+               "amount_in": "1", "params": {"algo": "best_direct"}}),
+    );
+    let first = ws_receive(&mut stream);
+    assert_eq!(first["type"], "quote");
+    assert_eq!(first["id"], "eth-usdc");
+    assert_eq!(first["block_number"], 100);
+    let pool = first["quote"]["route"]["steps"][0]["pool"].clone();
+
+    // An update to a pool off the route is not pushed
+    let unrelated = json!({"block_number": 101, "updates": [
+        {"id": "dai-usdt-1", "reserve0": "1", "reserve1": "1"},
+    ]});
+    assert_eq!(call(addr, "POST", "/pools", Some(unrelated)).0, 200);
+    let changed = json!({"block_number": 102, "updates": [
+        {"id": pool, "reserve0": "2000", "reserve1": "3000000"},
+    ]});
+    assert_eq!(call(addr, "POST", "/pools", Some(changed)).0, 200);
+
+    let pushed = ws_receive(&mut stream);
+    assert_eq!(pushed["id"], "eth-usdc");
+    assert_eq!(pushed["block_number"], 102);
+    assert_ne!(
+        pushed["quote"]["output_amount"],
+        first["quote"]["output_amount"]
+    );
+
+    ws_send(
+        &mut stream,
+        json!({"type": "unsubscribe", "id": "eth-usdc"}),
+    );
+    assert_eq!(
+        ws_receive(&mut stream),
+        json!({"type": "unsubscribed", "id": "eth-usdc"})
+    );
+    assert_eq!(call(addr, "GET", "/subscribe", None).0, 426);
+}
+
+#[test]
+fn test_cached_server_pushes_routes_through_updated_pools() {
+    let server = start_cached();
+    let addr = server.local_addr();
+    let mut stream = ws_connect(addr);
+
+    ws_send(
+        &mut stream,
+        json!({"type": "subscribe", "id": "eth-usdc", "from": "ETH", "to": "USDC",
+               "amount_in": "1"}),
+    );
+    let first = ws_receive(&mut stream);
+    assert_ne!(first["quote"]["route"]["steps"][0]["pool"], "eth-usdc-2");
+
+    // The pool is off the cached route but now beats it
+    let update = json!({"block_number": 101, "updates": [
+        {"id": "eth-usdc-2", "reserve0": "1000", "reserve1": "9000000"},
+    ]});
+    assert_eq!(call(addr, "POST", "/pools", Some(update)).0, 200);
+    let pushed = ws_receive(&mut stream);
+    assert_eq!(pushed["block_number"], 101);
+    assert_eq!(pushed["quote"]["route"]["steps"][0]["pool"], "eth-usdc-2");
+    server.shutdown();
+}
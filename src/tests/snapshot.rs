@@ -0,0 +1,114 @@
+use rust_decimal::dec;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::loader::load_graph;
+use crate::adapter::snapshot::*;
+use crate::domain::types::*;
+
+fn reference_graph() -> Graph {
+    load_graph(concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_pools.csv"))
+        .unwrap()
+        .with_block_number(19_000_000)
+}
+
+#[test]
+fn test_snapshot_round_trip_preserves_graph() {
+    let mut graph = reference_graph();
+    graph
+        .update_reserves(
+            &PoolId("eth-usdc-1"),
+            dec!(1234.000000000000000001),
+            dec!(42),
+        )
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("graph-{}.snapshot", std::process::id()));
+    save_snapshot(&graph, &path).unwrap();
+    let loaded = load_snapshot(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.block_number, 19_000_000);
+    assert_eq!(loaded.pools.len(), graph.pools.len());
+    for (id, pool) in &graph.pools {
+        assert_eq!(**loaded.pool(id).unwrap(), **pool);
+    }
+    for (token, pools) in &graph.adjacency {
+        let loaded_ids = loaded.adjacency[token].iter().map(|pool| &pool.id);
+        assert!(loaded_ids.eq(pools.iter().map(|pool| &pool.id)));
+    }
+    assert_eq!(encode(&loaded), encode(&graph));
+}
+
+#[test]
+fn test_snapshot_rejects_damaged_or_foreign_data() {
+    let bytes = encode(&reference_graph());
+
+    let mut flipped = bytes.clone();
+    flipped[40] ^= 0x01;
+    assert!(matches!(
+        decode(&flipped),
+        Err(SnapshotError::ChecksumMismatch { .. })
+    ));
+
+    let mut old = bytes.clone();
+    old[4..8].copy_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(
+        decode(&old),
+        Err(SnapshotError::UnsupportedVersion(0))
+    ));
+
+    assert!(matches!(decode(&bytes[..6]), Err(SnapshotError::Truncated)));
+
+    // Damage behind a valid checksum is caught while building the graph
+    let mut duplicate = bytes.clone();
+    let at = position(&duplicate, "eth-usdc-2");
+    duplicate[at..at + 10].copy_from_slice(b"eth-usdc-1");
+    assert!(matches!(
+        decode(&resealed(duplicate)),
+        Err(SnapshotError::Corrupt(message)) if message.contains("eth-usdc-1")
+    ));
+
+    // Flags byte 2 of the first pool's reserve0, after its id and token indices
+    let mut rescaled = bytes.clone();
+    rescaled[position(&bytes, "dai-usdc-1") + 10 + 8 + 2] = 29;
+    assert!(matches!(
+        decode(&resealed(rescaled)),
+        Err(SnapshotError::Corrupt(message)) if message.contains("scale 29")
+    ));
+    assert!(matches!(
+        decode(b"id,token0_symbol"),
+        Err(SnapshotError::NotASnapshot)
+    ));
+}
+
+#[test]
+fn test_snapshot_keeps_liquidity_thresholds() {
+    let thresholds = LiquidityThresholds {
+        min_reserve: Some(dec!(1500)),
+        min_tvl: Some(MinTvl {
+            value: dec!(5000000),
+            numeraire: Token("USDC"),
+        }),
+    };
+    let graph = reference_graph().with_liquidity(thresholds.clone());
+    assert!(!graph.pruned().pruned.is_empty());
+
+    let loaded = decode(&encode(&graph)).unwrap();
+    assert_eq!(loaded.liquidity(), &thresholds);
+    assert_eq!(loaded.pruned(), graph.pruned());
+}
+
+fn position(bytes: &[u8], text: &str) -> usize {
+    bytes
+        .windows(text.len())
+        .position(|window| window == text.as_bytes())
+        .unwrap()
+}
+
+/// `bytes` with the checksum recomputed over its edited body.
+fn resealed(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.truncate(bytes.len() - 8);
+    let checksum = fnv1a(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes
+}
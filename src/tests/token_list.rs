@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+
+use crate::adapter::graph::{Graph, GraphError};
+use crate::adapter::token_list::{TokenList, TokenListError, parse_token_list};
+use crate::application::algos::circular::circular;
+use crate::domain::types::*;
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+
+const DEFAULT_LIST: &str = r#"{
+    "name": "Default",
+    "timestamp": "2024-01-01T00:00:00Z",
+    "version": {"major": 1, "minor": 0, "patch": 0},
+    "tokens": [
+        {"chainId": 1, "address": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", "symbol": "ETH",
+         "name": "Wrapped Ether", "decimals": 18, "logoURI": "ipfs://weth"},
+        {"chainId": 1, "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "symbol": "USDC",
+         "name": "USD Coin", "decimals": 6, "tags": ["stablecoin"]},
+        {"chainId": 1, "address": "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", "symbol": "WBTC",
+         "name": "Wrapped BTC", "decimals": 8},
+        {"chainId": 137, "address": "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174", "symbol": "USDC",
+         "name": "USD Coin (PoS)", "decimals": 6}
+    ]
+}"#;
+
+const EXTRA_LIST: &str = r#"{
+    "name": "Extra",
+    "tokens": [
+        {"chainId": 1, "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "symbol": "USDC",
+         "name": "USD Coin", "decimals": 6, "tags": ["stablecoin", "circle"]},
+        {"chainId": 1, "address": "0x6B175474E89094C44Da98b954EedeAC495271d0F", "symbol": "DAI",
+         "name": "Dai", "decimals": 18}
+    ]
+}"#;
+
+fn pool(id: &'static str, token0: &'static str, token1: &'static str, reserve1: u32) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0: dec!(1000),
+        reserve1: reserve1.into(),
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+#[test]
+fn test_merged_token_lists_populate_graph_metadata() {
+    let lists = [DEFAULT_LIST, EXTRA_LIST].map(|list| parse_token_list(list).unwrap());
+    let merged = TokenList::merge_all(lists);
+    assert_eq!(merged.name, "Default");
+    assert_eq!(merged.tokens.len(), 5);
+    assert_eq!(merged.for_chain(1).count(), 4);
+
+    let graph = Graph::new(&[pool("eth-usdc", "ETH", "USDC", 2_000_000)])
+        .with_token_info(merged.for_chain(1).cloned())
+        .unwrap();
+    let usdc = graph.token_info(&Token("USDC")).unwrap();
+    assert_eq!(usdc.decimals, 6);
+    assert_eq!(usdc.tags, ["stablecoin", "circle"]);
+    assert_eq!(
+        usdc.address.to_string(),
+        "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+    );
+
+    let err = parse_token_list(
+        r#"{"tokens": [{"chainId": 1, "address": "0x12", "symbol": "X", "decimals": 18}]}"#,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, TokenListError::Token { index: 0, .. }),
+        "{err}"
+    );
+    assert!(parse_token_list(r#"{"name": "no tokens"}"#).is_err());
+}
+
+#[test]
+fn test_allow_list_restricts_every_hop() {
+    // The DAI leg pays more, but DAI is only on the extra list
+    let lists = [DEFAULT_LIST, EXTRA_LIST].map(|list| parse_token_list(list).unwrap());
+    let graph = Graph::new(&[
+        pool("eth-usdc", "ETH", "USDC", 2_000_000),
+        pool("usdc-wbtc", "USDC", "WBTC", 1_000),
+        pool("eth-dai", "ETH", "DAI", 4_000_000),
+        pool("dai-wbtc", "DAI", "WBTC", 2_000),
+    ])
+    .with_token_info(TokenList::merge_all(lists.clone()).for_chain(1).cloned())
+    .unwrap();
+    let allowed = ExecutionParams {
+        allowed_addresses: Some(Arc::new(lists[0].allow_list(1))),
+        ..Default::default()
+    };
+
+    for algo in [
+        RoutingAlgo::Auto,
+        RoutingAlgo::AStar,
+        RoutingAlgo::Beam,
+        RoutingAlgo::Bfs,
+        RoutingAlgo::Dfs,
+        RoutingAlgo::Dijkstra,
+    ] {
+        let params = ExecutionParams {
+            algo,
+            ..allowed.clone()
+        };
+        let (_, route) = DefaultRouter
+            .compute_route(
+                Side::Sell,
+                &graph,
+                &Token("ETH"),
+                &Token("WBTC"),
+                dec!(1),
+                params,
+            )
+            .unwrap();
+        assert!(
+            route.steps.iter().all(|step| step.to != Token("DAI")),
+            "{algo:?} routed through DAI"
+        );
+    }
+
+    let to_dai = DefaultRouter.compute_route(
+        Side::Sell,
+        &graph,
+        &Token("ETH"),
+        &Token("DAI"),
+        dec!(1),
+        allowed.clone(),
+    );
+    assert!(to_dai.is_none());
+    let cycle = circular(
+        Side::Sell,
+        &graph,
+        &Token("ETH"),
+        &Token("ETH"),
+        dec!(1),
+        allowed,
+    );
+    assert!(cycle.is_none_or(|route| route.steps.iter().all(|step| step.to != Token("DAI"))));
+}
+
+#[test]
+fn test_allow_list_checks_addresses_not_symbols() {
+    const SPOOF_LIST: &str = r#"{
+        "name": "Spoof",
+        "tokens": [
+            {"chainId": 1, "address": "0x00000000000000000000000000000000deadbeef", "symbol": "USDC",
+             "name": "USD Coin", "decimals": 6}
+        ]
+    }"#;
+    let default = parse_token_list(DEFAULT_LIST).unwrap();
+    let spoof = parse_token_list(SPOOF_LIST).unwrap();
+    let pools = [pool("eth-usdc", "ETH", "USDC", 2_000_000)];
+    let allowed = ExecutionParams {
+        allowed_addresses: Some(Arc::new(default.allow_list(1))),
+        ..Default::default()
+    };
+    let quote = |graph: &Graph| {
+        DefaultRouter.compute_route(
+            Side::Sell,
+            graph,
+            &Token("ETH"),
+            &Token("USDC"),
+            dec!(1),
+            allowed.clone(),
+        )
+    };
+
+    let genuine = Graph::new(&pools)
+        .with_token_info(default.for_chain(1).cloned())
+        .unwrap();
+    assert!(quote(&genuine).is_some());
+
+    // A pool token named USDC but deployed at another address is not allowed
+    let spoofed = Graph::new(&pools)
+        .with_token_info(
+            default
+                .for_chain(1)
+                .filter(|info| info.symbol != Token("USDC"))
+                .cloned(),
+        )
+        .unwrap()
+        .with_token_info(spoof.tokens.clone())
+        .unwrap();
+    assert!(quote(&spoofed).is_none());
+    // Neither is one without metadata
+    assert!(quote(&Graph::new(&pools)).is_none());
+
+    let err = Graph::new(&pools)
+        .with_token_info(default.for_chain(1).cloned().chain(spoof.tokens))
+        .unwrap_err();
+    assert!(
+        matches!(&err, GraphError::DuplicateSymbol { symbol, .. } if *symbol == Token("USDC")),
+        "{err}"
+    );
+}
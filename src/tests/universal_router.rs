@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use rust_decimal::dec;
+
+use crate::adapter::graph::Graph;
+use crate::adapter::router_v2::{CalldataError, SwapOptions};
+use crate::adapter::universal_router::{
+    ADDRESS_THIS, Command, ExecuteCall, MSG_SENDER, RouteLeg, execute_call,
+};
+use crate::domain::types::*;
+use crate::tests::router_v2::{DAI, DEADLINE, RECIPIENT, USDC, WETH, address, info};
+
+fn fixture(hex: &str) -> String {
+    hex.split_whitespace().collect()
+}
+
+/// Expected calldata was produced independently with `ethabi`.
+#[test]
+fn test_execute_calls_match_reference_encodings() {
+    let split_sell = ExecuteCall {
+        commands: vec![
+            Command::WrapEth {
+                recipient: ADDRESS_THIS,
+                amount_min: 1_000_000_000_000_000_000,
+            },
+            Command::V2SwapExactIn {
+                recipient: ADDRESS_THIS,
+                amount_in: 600_000_000_000_000_000,
+                amount_out_min: 0,
+                path: vec![address(WETH), address(USDC)],
+                payer_is_user: false,
+            },
+            Command::V2SwapExactIn {
+                recipient: ADDRESS_THIS,
+                amount_in: 400_000_000_000_000_000,
+                amount_out_min: 0,
+                path: vec![address(WETH), address(DAI), address(USDC)],
+                payer_is_user: false,
+            },
+            Command::Sweep {
+                token: address(USDC),
+                recipient: RECIPIENT,
+                amount_min: 1_990_000_000,
+            },
+        ],
+        deadline: DEADLINE,
+        value: 1_000_000_000_000_000_000,
+    };
+    assert_eq!(
+        hex::encode(split_sell.encode()),
+        fixture(include_str!("fixtures/universal_router_split_sell.hex"))
+    );
+
+    let exact_out_eth = ExecuteCall {
+        commands: vec![
+            Command::V2SwapExactOut {
+                recipient: ADDRESS_THIS,
+                amount_out: 500_000_000_000_000_000,
+                amount_in_max: 1_005_000_000,
+                path: vec![address(USDC), address(WETH)],
+                payer_is_user: true,
+            },
+            Command::UnwrapWeth {
+                recipient: RECIPIENT,
+                amount_min: 500_000_000_000_000_000,
+            },
+        ],
+        deadline: DEADLINE,
+        value: 0,
+    };
+    assert_eq!(
+        hex::encode(exact_out_eth.encode()),
+        fixture(include_str!("fixtures/universal_router_exact_out_eth.hex"))
+    );
+}
+
+fn route(
+    pools: &[&Arc<Pool>],
+    tokens: &[&'static str],
+    output_amount: rust_decimal::Decimal,
+) -> Route {
+    Route {
+        steps: pools
+            .iter()
+            .zip(tokens.windows(2))
+            .map(|(pool, pair)| SwapStep {
+                from: Token(pair[0]),
+                to: Token(pair[1]),
+                pool: Arc::clone(pool),
+            })
+            .collect(),
+        output_amount,
+        partial: false,
+    }
+}
+
+#[test]
+fn test_execute_call_for_split_and_single_routes() {
+    let pool = |id, token0, token1| {
+        Arc::new(Pool {
+            id: PoolId(id),
+            token0: Token(token0),
+            token1: Token(token1),
+            reserve0: dec!(1000),
+            reserve1: dec!(2000000),
+            fee_bps: dec!(30),
+            venue: Venue::UNISWAP,
+            chain_id: MAINNET,
+        })
+    };
+    let (weth_usdc, weth_dai, dai_usdc) = (
+        pool("weth-usdc", "WETH", "USDC"),
+        pool("weth-dai", "WETH", "DAI"),
+        pool("dai-usdc", "DAI", "USDC"),
+    );
+    let graph = Graph::new(&[weth_usdc.clone(), weth_dai.clone(), dai_usdc.clone()])
+        .with_token_info([
+            info("USDC", address(USDC), 6),
+            info("WETH", address(WETH), 18),
+            info("DAI", address(DAI), 18),
+        ])
+        .unwrap();
+    let direct = route(&[&weth_usdc], &["WETH", "USDC"], dec!(1200));
+    let via_dai = route(&[&weth_dai, &dai_usdc], &["WETH", "DAI", "USDC"], dec!(800));
+    let options = SwapOptions {
+        recipient: RECIPIENT,
+        deadline: DEADLINE,
+        slippage: Slippage {
+            tolerance_bps: dec!(50),
+        },
+        native: Some(Token("WETH")),
+    };
+    let legs = [
+        RouteLeg {
+            route: &direct,
+            amount_in: dec!(0.6),
+        },
+        RouteLeg {
+            route: &via_dai,
+            amount_in: dec!(0.4),
+        },
+    ];
+
+    // ETH in, split over two legs: wrap, swap into the router, sweep the total minimum
+    let call = execute_call(&graph, Side::Sell, &legs, &options).unwrap();
+    let codes: Vec<u8> = call.commands.iter().map(Command::code).collect();
+    assert_eq!(codes, [0x0b, 0x08, 0x08, 0x04]);
+    assert_eq!(call.value, 1_000_000_000_000_000_000);
+    assert_eq!(
+        call.commands[3],
+        Command::Sweep {
+            token: address(USDC),
+            recipient: RECIPIENT,
+            amount_min: 1_990_000_000,
+        }
+    );
+
+    // A single token leg pays out directly with its own minimum
+    let tokens_only = SwapOptions {
+        native: None,
+        ..options.clone()
+    };
+    let call = execute_call(&graph, Side::Sell, &legs[..1], &tokens_only).unwrap();
+    assert_eq!(
+        call.commands,
+        [Command::V2SwapExactIn {
+            recipient: RECIPIENT,
+            amount_in: 600_000_000_000_000_000,
+            amount_out_min: 1_194_000_000,
+            path: vec![address(WETH), address(USDC)],
+            payer_is_user: true,
+        }]
+    );
+    assert_eq!(call.value, 0);
+
+    // Buying with ETH refunds what the exact output swaps did not spend
+    let call = execute_call(&graph, Side::Buy, &legs[..1], &options).unwrap();
+    let codes: Vec<u8> = call.commands.iter().map(Command::code).collect();
+    assert_eq!(codes, [0x0b, 0x09, 0x0c]);
+    assert_eq!(call.value, 603_000_000_000_000_000);
+    assert_eq!(
+        call.commands[2],
+        Command::UnwrapWeth {
+            recipient: MSG_SENDER,
+            amount_min: 0,
+        }
+    );
+
+    let reversed = route(&[&weth_usdc], &["USDC", "WETH"], dec!(0.5));
+    let mismatched = [
+        legs[0].clone(),
+        RouteLeg {
+            route: &reversed,
+            amount_in: dec!(1000),
+        },
+    ];
+    assert!(matches!(
+        execute_call(&graph, Side::Sell, &mismatched, &options),
+        Err(CalldataError::MismatchedLegs { .. })
+    ));
+}
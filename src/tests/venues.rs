@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::graph::Graph;
+use crate::adapter::loader::parse_json;
+use crate::adapter::router_v2::{CalldataError, router_address};
+use crate::application::route_engine::execute;
+use crate::domain::types::*;
+use crate::domain::venue::{VenueConfig, Venues};
+
+fn pool(id: &'static str, venue: Venue, reserve1: Decimal) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token("ETH"),
+        token1: Token("USDC"),
+        reserve0: dec!(1000),
+        reserve1,
+        fee_bps: dec!(30),
+        venue,
+        chain_id: MAINNET,
+    })
+}
+
+#[test]
+fn test_venue_filters_apply_to_every_algorithm() {
+    let graph = Graph::new(&[
+        pool("uni", Venue::UNISWAP, dec!(2000000)),
+        pool("sushi", Venue::SUSHISWAP, dec!(2100000)),
+    ]);
+    let venues = |venues: &[Venue]| Some(Arc::new(venues.iter().cloned().collect::<HashSet<_>>()));
+    for algo in [
+        RoutingAlgo::BestDirect,
+        RoutingAlgo::Bfs,
+        RoutingAlgo::Dfs,
+        RoutingAlgo::Dijkstra,
+        RoutingAlgo::AStar,
+        RoutingAlgo::Beam,
+        RoutingAlgo::Auto,
+    ] {
+        let pool_used = |params: ExecutionParams| {
+            let params = ExecutionParams { algo, ..params };
+            execute(
+                Side::Sell,
+                &graph,
+                &Token("ETH"),
+                &Token("USDC"),
+                dec!(1),
+                params,
+            )
+            .map(|(_, route)| route.steps[0].pool.id.clone())
+        };
+        assert!(pool_used(ExecutionParams::default()).is_some());
+        let only_uniswap = ExecutionParams {
+            allowed_venues: venues(&[Venue::UNISWAP]),
+            ..Default::default()
+        };
+        assert_eq!(pool_used(only_uniswap), Some(PoolId("uni")), "{:?}", algo);
+        let no_sushi = ExecutionParams {
+            excluded_venues: venues(&[Venue::SUSHISWAP]),
+            ..Default::default()
+        };
+        assert_eq!(pool_used(no_sushi), Some(PoolId("uni")), "{:?}", algo);
+        let only_sushi = ExecutionParams {
+            allowed_venues: venues(&[Venue::SUSHISWAP]),
+            ..Default::default()
+        };
+        assert_eq!(pool_used(only_sushi), Some(PoolId("sushi")), "{:?}", algo);
+        let nothing = ExecutionParams {
+            allowed_venues: venues(&[Venue::PANCAKESWAP]),
+            ..Default::default()
+        };
+        assert_eq!(pool_used(nothing), None, "{:?}", algo);
+    }
+}
+
+#[test]
+fn test_venue_defaults_and_routers() {
+    let dump = concat!(
+        r#"{"id": "a", "token0": {"symbol": "ETH"}, "token1": {"symbol": "USDC"}, "reserve0": "1", "reserve1": "2"}"#,
+        "\n",
+        r#"{"id": "b", "token0": {"symbol": "ETH"}, "token1": {"symbol": "DAI"}, "reserve0": "1", "reserve1": "2", "venue": "sushiswap", "fee_bps": 25}"#,
+    );
+    let graph = parse_json(dump).unwrap().graph().unwrap();
+    let a = graph.pool(&PoolId("a")).unwrap();
+    assert_eq!((a.venue.clone(), a.fee_bps), (Venue::UNISWAP, dec!(30)));
+    assert_eq!(graph.pool(&PoolId("b")).unwrap().venue, Venue::SUSHISWAP);
+    // No built-in configuration to take SushiSwap's fee from
+    let err = parse_json(&dump.replace(r#", "fee_bps": 25"#, "")).unwrap_err();
+    assert!(err.to_string().starts_with("line 2:"), "{}", err);
+
+    let route = |pools: &[Arc<Pool>]| Route {
+        steps: pools
+            .iter()
+            .map(|pool| SwapStep {
+                from: pool.token0.clone(),
+                to: pool.token1.clone(),
+                pool: Arc::clone(pool),
+            })
+            .collect(),
+        output_amount: Decimal::ONE,
+        partial: false,
+    };
+    let uni = pool("uni", Venue::UNISWAP, dec!(2000000));
+    let sushi = pool("sushi", Venue::SUSHISWAP, dec!(2000000));
+    let venues = Venues::builtin();
+    assert_eq!(
+        router_address(&venues, MAINNET, &route(&[Arc::clone(&uni)])),
+        Ok(VenueConfig::uniswap_mainnet().router)
+    );
+    assert_eq!(
+        router_address(&venues, MAINNET, &route(&[Arc::clone(&sushi)])),
+        Err(CalldataError::UnsupportedVenue(Venue::SUSHISWAP))
+    );
+    assert_eq!(
+        router_address(&venues, MAINNET, &route(&[uni, sushi])),
+        Err(CalldataError::MixedVenues(Venue::UNISWAP, Venue::SUSHISWAP))
+    );
+}
+
+#[test]
+fn test_builtin_venues() {
+    let venues = Venues::builtin();
+    assert_eq!(venues.fee_bps(BSC, &Venue::PANCAKESWAP), Some(dec!(25)));
+    assert_eq!(venues.fee_bps(MAINNET, &Venue::PANCAKESWAP), None);
+
+    let uniswap = VenueConfig::uniswap_mainnet();
+    for chain_id in [MAINNET, ARBITRUM, BASE] {
+        let config = venues.get(chain_id, &Venue::UNISWAP).unwrap();
+        assert_eq!(config.init_code_hash, uniswap.init_code_hash);
+        assert_eq!(venues.by_factory(chain_id, &config.factory), Some(config));
+    }
+    // Factories are only recognised on their own chain
+    assert_eq!(venues.by_factory(BASE, &uniswap.factory), None);
+
+    let pancake = VenueConfig::pancakeswap_bsc();
+    let mut pool = (*pool("cake", Venue::PANCAKESWAP, dec!(2000000))).clone();
+    pool.chain_id = BSC;
+    let route = Route {
+        steps: vec![SwapStep {
+            from: pool.token0.clone(),
+            to: pool.token1.clone(),
+            pool: Arc::new(pool),
+        }],
+        output_amount: Decimal::ONE,
+        partial: false,
+    };
+    assert_eq!(router_address(&venues, BSC, &route), Ok(pancake.router));
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use rust_decimal::{Decimal, dec};
+
+use crate::adapter::graph::Graph;
+use crate::adapter::pair_math::PairMath;
+use crate::application::verify::verify_route;
+use crate::domain::types::*;
+use crate::port::routing::{DefaultRouter, RoutingStrategy};
+use crate::port::verification::ExecutionError;
+
+fn pool(
+    id: &'static str,
+    token0: &'static str,
+    token1: &'static str,
+    reserves: (Decimal, Decimal),
+) -> Arc<Pool> {
+    Arc::new(Pool {
+        id: PoolId(id),
+        token0: Token(token0),
+        token1: Token(token1),
+        reserve0: reserves.0,
+        reserve1: reserves.1,
+        fee_bps: dec!(30),
+        venue: Venue::UNISWAP,
+        chain_id: MAINNET,
+    })
+}
+
+fn info(symbol: &'static str, decimals: u32) -> TokenInfo {
+    TokenInfo {
+        chain_id: 1,
+        address: Address([0; 20]),
+        symbol: Token(symbol),
+        decimals,
+        tags: Vec::new(),
+    }
+}
+
+fn best_route(graph: &Graph, from: &'static str, to: &'static str, amount: Decimal) -> Route {
+    DefaultRouter
+        .compute_route(
+            Side::Sell,
+            graph,
+            &Token(from),
+            &Token(to),
+            amount,
+            ExecutionParams::default(),
+        )
+        .unwrap()
+        .1
+}
+
+#[test]
+fn test_verify_route_matches_pair_integer_math() {
+    // Deep pools overflow 128 bits in getAmountOut's numerator
+    let graph = Graph::new(&[
+        pool(
+            "weth-usdc",
+            "WETH",
+            "USDC",
+            (dec!(1000000000), dec!(2000000000000)),
+        ),
+        pool(
+            "usdc-dai",
+            "USDC",
+            "DAI",
+            (dec!(3000000000000), dec!(3000000000000)),
+        ),
+    ])
+    .with_token_info([info("WETH", 18), info("USDC", 6), info("DAI", 18)])
+    .unwrap();
+    let route = best_route(&graph, "WETH", "DAI", dec!(1000000));
+
+    let verification = verify_route(&PairMath, &graph, &route, dec!(1000000)).unwrap();
+    assert_eq!(verification.hops.len(), 2);
+    assert_eq!(verification.expected, route.output_amount);
+    // On chain every hop rounds down to whole token units
+    assert!(verification.executed <= verification.expected);
+    assert!(verification.is_within(dec!(0.000000001)));
+    assert_eq!(verification.hops[0].executed.scale(), 6);
+}
+
+#[test]
+fn test_verify_route_reports_rounding_and_unsupported_pools() {
+    let graph = Graph::new(&[pool("a-b", "A", "B", (dec!(100), dec!(200)))])
+        .with_token_info([info("A", 0), info("B", 0)])
+        .unwrap();
+    let route = best_route(&graph, "A", "B", dec!(1));
+
+    // 1 * 9970 * 200 / (100 * 10000 + 9970) = 1.97..., truncated to a whole unit
+    let verification = verify_route(&PairMath, &graph, &route, dec!(1)).unwrap();
+    assert_eq!(verification.executed, dec!(1));
+    assert!(verification.discrepancy() > dec!(0.97));
+    assert!(!verification.is_within(dec!(0.01)));
+
+    let mut fractional = Pool::clone(&route.steps[0].pool);
+    fractional.fee_bps = dec!(30.5);
+    let graph = Graph::new(&[Arc::new(fractional)]);
+    assert!(matches!(
+        verify_route(&PairMath, &graph, &route, dec!(1)),
+        Err(ExecutionError::Unsupported(_))
+    ));
+}